@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
 use clap_verbosity_flag::Verbosity;
+use modelcards::{merge::MergeMode, utils::DataFormat, validate::{Profile, ReportFormat}};
 
 #[derive(Parser)]
 #[clap(version, author, about)]
@@ -37,17 +38,33 @@ pub enum Command {
 
     /// Deletes the output directory if there is one and builds the modelcard
     Build {
-        /// The source modelcard data file to be build (defaults to all in 'data' dir in project root)
+        /// The source modelcard data file to be build, '-' reads from stdin (defaults to all in 'data' dir in project root)
         #[clap(short = 's', long)]
         source: Option<String>,
 
-        /// Outputs the generated site in the given path (by default 'card' dir in project root)
+        /// Outputs the generated site in the given path, '-' for stdout (by default 'card' dir in project root)
         #[clap(short = 'o', long)]
         target: Option<String>,
 
         /// Force building the modelcard even if output directory is non-empty
         #[clap(short = 'f', long)]
         force: Option<bool>,
+
+        /// Fail if a data file changes the type of a value set by its parents and don't build on warnings (implies --deny-warnings)
+        #[clap(long)]
+        strict: bool,
+
+        /// Exit with code 3 without building if warnings were found, like deprecated or unknown fields and lint rules on level warning
+        #[clap(long)]
+        deny_warnings: bool,
+
+        /// Fail if less than this percentage of the optional schema fields is filled
+        #[clap(long, value_name = "PERCENT")]
+        min_score: Option<f64>,
+
+        /// Profile layered over the project schema: eu-ai-act, nist-ai-rmf or internal (defaults to google or settings in config.toml)
+        #[clap(short = 'p', long)]
+        profile: Option<Profile>,
     },
 
     /// Try to build the project without rendering it. Checks inputs
@@ -55,39 +72,224 @@ pub enum Command {
         /// The source modelcard data file to be verified (defaults to sample.json or settings in config.toml)
         #[clap(short = 's', long)]
         source: Option<String>,
+
+        /// Output format of the validation report: text, json or sarif
+        #[clap(long, default_value = "text")]
+        format: ReportFormat,
+
+        /// Profile layered over the project schema: eu-ai-act, nist-ai-rmf or internal (defaults to google or settings in config.toml)
+        #[clap(short = 'p', long)]
+        profile: Option<Profile>,
+
+        /// Exit with code 3 if warnings were found, like deprecated or unknown fields and lint rules on level warning
+        #[clap(long)]
+        deny_warnings: bool,
+
+        /// Fail if a data file changes the type of a value set by its parents and exit with code 3 on warnings (implies --deny-warnings)
+        #[clap(long)]
+        strict: bool,
     },
 
     /// Validate the modelcard data file against the schema
     Validate {
-        /// The source modelcard data file to be verified
+        /// The source modelcard data files, directories or glob patterns to be verified ('-' reads from stdin)
         #[clap(value_delimiter = ' ', num_args = 1..)]
         sources: Vec<String>,
 
         /// The schema file to validate against (defaults to build-in schema)
         #[clap(short = 's', long)]
         schema: Option<String>,
+
+        /// Profile layered over the schema: google, huggingface (the YAML front matter of a Hub README.md), eu-ai-act, nist-ai-rmf or internal
+        #[clap(short = 'p', long)]
+        profile: Option<Profile>,
+
+        /// Directory searched for schemas referenced by the schema, besides its own directory (repeatable)
+        #[clap(long, value_name = "DIR")]
+        schema_path: Vec<String>,
+
+        /// Output format of the validation report: text, json or sarif
+        #[clap(long, default_value = "text")]
+        format: ReportFormat,
+
+        /// Exit with code 3 if warnings were found, like deprecated or unknown fields
+        #[clap(long)]
+        deny_warnings: bool,
+
+        /// Fail if a later file changes the type of a value and exit with code 3 on warnings (implies --deny-warnings)
+        #[clap(long)]
+        strict: bool,
+
+        /// How the source files are merged: deep or patch (RFC 7396, null removes fields)
+        #[clap(long, default_value = "deep")]
+        merge_mode: MergeMode,
+
+        /// Format of the source files: json, yaml, toml or markdown (detected by file extension if not set)
+        #[clap(long)]
+        input_format: Option<DataFormat>,
+
+        /// Sort files of directory and glob sources by numeric prefix (e.g. 00-defaults.json) instead of by name
+        #[clap(long)]
+        numeric_prefixes: bool,
+    },
+
+    /// Check the modelcard against the built-in and configured lint rules
+    Lint {
+        /// The source modelcard data files, directories or glob patterns to be checked ('-' reads from stdin)
+        #[clap(value_delimiter = ' ', num_args = 1..)]
+        sources: Vec<String>,
+
+        /// Output format of the lint report: text, json or sarif
+        #[clap(long, default_value = "text")]
+        format: ReportFormat,
+
+        /// Exit with code 3 if rules on level warning failed
+        #[clap(long)]
+        deny_warnings: bool,
+
+        /// How the source files are merged: deep or patch (RFC 7396, null removes fields)
+        #[clap(long, default_value = "deep")]
+        merge_mode: MergeMode,
+
+        /// Format of the source files: json, yaml, toml or markdown (detected by file extension if not set)
+        #[clap(long)]
+        input_format: Option<DataFormat>,
+
+        /// Sort files of directory and glob sources by numeric prefix (e.g. 00-defaults.json) instead of by name
+        #[clap(long)]
+        numeric_prefixes: bool,
+    },
+
+    /// Score the completeness of the modelcard as percentage of optional schema fields filled per section
+    Score {
+        /// The source modelcard data files, directories or glob patterns to be scored ('-' reads from stdin)
+        #[clap(value_delimiter = ' ', num_args = 1..)]
+        sources: Vec<String>,
+
+        /// The schema file declaring the fields (defaults to build-in schema)
+        #[clap(short = 's', long)]
+        schema: Option<String>,
+
+        /// Fail if less than this percentage of the optional fields is filled
+        #[clap(long, value_name = "PERCENT")]
+        min_score: Option<f64>,
+
+        /// Print the score as JSON
+        #[clap(long)]
+        json: bool,
+
+        /// How the source files are merged: deep or patch (RFC 7396, null removes fields)
+        #[clap(long, default_value = "deep")]
+        merge_mode: MergeMode,
+
+        /// Format of the source files: json, yaml, toml or markdown (detected by file extension if not set)
+        #[clap(long)]
+        input_format: Option<DataFormat>,
+
+        /// Sort files of directory and glob sources by numeric prefix (e.g. 00-defaults.json) instead of by name
+        #[clap(long)]
+        numeric_prefixes: bool,
     },
 
     /// Render the modelcard using template
     Render {
-        /// The source modelcard data file to be verified
+        /// The source modelcard data files, directories or glob patterns to be verified ('-' reads from stdin)
         #[clap(value_delimiter = ' ', num_args = 1..)]
         sources: Vec<String>,
 
         /// The jinjia template file to use (defaults to build-in markdown template)
         #[clap(short = 't', long)]
         template: Option<String>,
+
+        /// The output file to write the rendered modelcard to, '-' for stdout (defaults to <name>.md of the last source)
+        #[clap(short = 'o', long)]
+        target: Option<String>,
+
+        /// How the source files are merged: deep or patch (RFC 7396, null removes fields)
+        #[clap(long, default_value = "deep")]
+        merge_mode: MergeMode,
+
+        /// Format of the source files: json, yaml, toml or markdown (detected by file extension if not set)
+        #[clap(long)]
+        input_format: Option<DataFormat>,
+
+        /// Sort files of directory and glob sources by numeric prefix (e.g. 00-defaults.json) instead of by name
+        #[clap(long)]
+        numeric_prefixes: bool,
     },
 
     /// Merge multiple modelcard data files into one
     Merge {
-        /// The source modelcard data files to be merged
+        /// The source modelcard data files, directories or glob patterns to be merged ('-' reads from stdin)
         #[clap(value_delimiter = ' ', num_args = 1..)]
         sources: Vec<String>,
 
-        /// The output file to write the merged data to
+        /// The output file to write the merged data to ('-' for stdout)
         #[clap(short = 'o', long)]
         target: Option<String>,
+
+        /// How arrays are merged: replace, append, prepend, union or merge-by-key:<key> (prefix with a JSON pointer and '=' to apply to a single array only)
+        #[clap(short = 'a', long = "arrays", value_name = "[POINTER=]STRATEGY")]
+        arrays: Vec<String>,
+
+        /// How the source files are merged: deep or patch (RFC 7396, null removes fields)
+        #[clap(long, default_value = "deep")]
+        merge_mode: MergeMode,
+
+        /// Format of the source files: json, yaml, toml or markdown (detected by file extension if not set)
+        #[clap(long)]
+        input_format: Option<DataFormat>,
+
+        /// Sort files of directory and glob sources by numeric prefix (e.g. 00-defaults.json) instead of by name
+        #[clap(long)]
+        numeric_prefixes: bool,
+
+        /// Print every value of the merged modelcard with the source file that set it and the values it shadowed
+        #[clap(long)]
+        explain: bool,
+
+        /// Fail if a later file changes the type of a value (e.g. an object replaced by a string) instead of warning
+        #[clap(long)]
+        strict: bool,
+
+        /// Common base of a three-way merge of exactly two sources (ours and theirs)
+        #[clap(long)]
+        base: Option<String>,
+
+        /// Write conflicts of a three-way merge as {"$conflict": {"base": ..., "ours": ..., "theirs": ...}} objects
+        #[clap(long, requires = "base")]
+        conflict_markers: bool,
+    },
+
+    /// Upgrade modelcard data files to a newer schema version, rewriting them in place or into a directory
+    Migrate {
+        /// The modelcard data files, directories or glob patterns to be migrated
+        #[clap(value_delimiter = ' ', num_args = 1..)]
+        sources: Vec<String>,
+
+        /// Directory to write the migrated files to instead of rewriting them in place
+        #[clap(short = 'o', long, value_name = "DIR")]
+        target: Option<String>,
+
+        /// Schema version to migrate to (defaults to the latest version)
+        #[clap(long, value_name = "VERSION")]
+        to: Option<String>,
+
+        /// Schema version of files without schema_version (such files are skipped otherwise)
+        #[clap(long, value_name = "VERSION")]
+        from: Option<String>,
+
+        /// File with custom migrations (JSON, YAML or TOML) instead of the built-in migrations of the Google schema
+        #[clap(long, value_name = "FILE")]
+        migrations: Option<String>,
+
+        /// Format of the source files: json, yaml or toml (detected by file extension if not set)
+        #[clap(long)]
+        input_format: Option<DataFormat>,
+
+        /// Sort files of directory and glob sources by numeric prefix (e.g. 00-defaults.json) instead of by name
+        #[clap(long)]
+        numeric_prefixes: bool,
     },
 
     /// Generate shell completion
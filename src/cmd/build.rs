@@ -1,34 +1,93 @@
 use std::{fs::create_dir_all, path::{Path, PathBuf}};
 use modelcards::{
-    render::render_template,
-    validate::check_against_schema
+    merge::{from_paths_with_provenance, MergeOptions},
+    render::render_value_to_template,
+    utils::{write_target, STDIO},
+    validate::{score_against_schema, Profile, SchemaOptions}
 };
 use anyhow::{bail, Result};
 
-pub fn build_project(path: &Path, modelcard: Option<String>, target: Option<String>, force: bool) -> Result<bool> {
+/// Options of a project build
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions {
+    /// Overwrite an existing modelcard file
+    pub force: bool,
+    /// Fail on type conflicts between data layers instead of warning and don't render on validation warnings
+    pub strict: bool,
+    /// Don't render the modelcard if validation found warnings (deprecated or unknown fields, lint rules on level warning, ...)
+    pub deny_warnings: bool,
+    /// Fail if less than this percentage of the optional schema fields is filled
+    pub min_score: Option<f64>,
+    /// Directories searched for schemas referenced by the project schema, relative to the project
+    pub schema_path: Vec<String>,
+    /// Profile layered over the project schema
+    pub profile: Profile,
+}
+
+/// Validate and render the modelcard of a project. Returns `false` without rendering if validation found warnings
+/// and `deny_warnings` or `strict` is set.
+pub fn build_project(path: &Path, modelcard: Option<String>, target: Option<String>, options: &BuildOptions) -> Result<bool> {
 
     // check if project directory exists
     if !path.is_dir() {
         bail!("Project directory does not exist at '{}'", path.display());
     }
 
-    let modelcard = opt_get_path(modelcard, "sample.json", path)?;
-    let file_name = Path::new(modelcard.file_name().ok_or_else(|| anyhow::anyhow!("Invalid modelcard path"))?).with_extension("md");
+    // piped data has no file name to derive the name of the output file from
+    let (modelcard, file_name) = if modelcard.as_deref() == Some(STDIO) {
+        (PathBuf::from(STDIO), PathBuf::from("modelcard.md"))
+    } else {
+        let modelcard = opt_get_path(modelcard, "sample.json", path)?;
+        let file_name = Path::new(modelcard.file_name().ok_or_else(|| anyhow::anyhow!("Invalid modelcard path"))?).with_extension("md");
+        (modelcard, file_name)
+    };
     //let target_file = opt_get_path(target, modelcard.file_name().unwrap().to_str().unwrap(), path.join("cards").as_path())?;
 
     let target = target.unwrap_or_else(|| path.join("cards").join(&file_name).to_string_lossy().to_string());
-    let out_dir = Path::new(&target).parent().ok_or_else(|| anyhow::anyhow!("Invalid target path"))?;
-    create_dir_all(out_dir)?;
+    let target_file = if target == STDIO {
+        PathBuf::from(STDIO)
+    } else {
+        let out_dir = Path::new(&target).parent().ok_or_else(|| anyhow::anyhow!("Invalid target path"))?;
+        create_dir_all(out_dir)?;
+
+        // check if output file exists and if force is not set
+        let target_file = out_dir.join(file_name);
+        if target_file.exists() && !options.force {
+            bail!("Modelcard file '{}' already exists. Use --force to overwrite.", target_file.display());
+        }
+        target_file
+    };
+
+    // load the data once, rejecting type conflicts between layers in strict mode and resolving `${config:project_dir}`
+    let mut merge_options = MergeOptions { strict: options.strict, interpolate: true, ..Default::default() };
+    merge_options.variables.insert("project_dir".to_string(), path.display().to_string());
+    let (data, provenance) = match from_paths_with_provenance(vec![modelcard.to_string_lossy().to_string()], &merge_options) {
+        Ok(result) => result,
+        Err(e) => bail!("Could not load modelcard data!\n{:?}", e),
+    };
 
-    // check if output file exists and if force is not set
-    let target_file = out_dir.join(file_name);
-    if target_file.exists() && !force {
-        bail!("Modelcard file '{}' already exists. Use --force to overwrite.", target_file.display());
+    // check if data validates agains schema, pointing to the layer files of invalid values
+    let schema_options = SchemaOptions::from_file(path.join("schema/modelcard.schema.json"))
+        .with_search_path(options.schema_path.iter().map(|dir| path.join(dir)));
+    let schema = schema_options.load()?;
+    let mut report = options.profile.validate(&data, Some(schema.clone()), &schema_options)?;
+    report.locate(&provenance);
+    if !report.is_valid() {
+        bail!("Project could not be validated!\n{}", report);
+    }
+    for warning in report.warnings() {
+        log::warn!("{}", warning.to_string().trim_end());
+    }
+    if (options.deny_warnings || options.strict) && report.has_warnings() {
+        return Ok(false);
     }
 
-    // check if data validates agains schema
-    if let Err(e) = check_against_schema(path, modelcard.as_path()) {
-        bail!("Project could not be validated!\n{:?}", e);
+    // check if enough optional fields are filled
+    if let Some(min_score) = options.min_score {
+        let score = score_against_schema(&data, Some(schema))?;
+        if !score.meets(min_score) {
+            bail!("Modelcard is only {:.1}% complete, the minimum is {}%!\n{}", score.percent(), min_score, score);
+        }
     }
 
     log::info!("Building project...");
@@ -39,8 +98,8 @@ pub fn build_project(path: &Path, modelcard: Option<String>, target: Option<Stri
     log::info!("Output: {}", target_file.display());
 
     // render the template
-    match render_template(&path.join("templates/modelcard.md.jinja"), modelcard.as_path()) {
-        Ok(result) => modelcards::utils::create_file(&target_file, &result)?,
+    match render_value_to_template(data, Some(&path.join("templates/modelcard.md.jinja"))) {
+        Ok(result) => write_target(&target_file.to_string_lossy(), &result)?,
         Err(e) => bail!("Could not render template: {:?}", e),
     }
 
@@ -99,7 +158,7 @@ mod tests {
     fn build_project_with_defaults() {
         let path = get_temp_dir("test_build_project_with_defaults", true);
         create_new_project(path.to_str().expect("Invalid path"), false).expect("Could not populate test directory");
-        build_project(&path, None, None, false).expect("Could not build project");
+        build_project(&path, None, None, &BuildOptions::default()).expect("Could not build project");
         assert!(path.join("cards/sample.md").exists());
     }
 
@@ -108,7 +167,53 @@ mod tests {
         let path = get_temp_dir("test_build_project_with_custom_data", true);
         create_new_project(path.to_str().expect("Invalid path"), false).expect("Could not populate test directory");
         create_file(path.join("modelcard.json").as_path(), schema::get_sample()).expect("Could not create modelcard data file");
-        build_project(&path, Some("modelcard.json".to_string()), None, false).expect("Could not build project");
+        build_project(&path, Some("modelcard.json".to_string()), None, &BuildOptions::default()).expect("Could not build project");
         assert!(path.join("cards/modelcard.md").exists());
     }
+
+    #[test]
+    fn build_project_to_stdout() {
+        let path = get_temp_dir("test_build_project_to_stdout", true);
+        create_new_project(path.to_str().expect("Invalid path"), false).expect("Could not populate test directory");
+        build_project(&path, None, Some(STDIO.to_string()), &BuildOptions::default()).expect("Could not build project");
+        assert!(!path.join("cards").exists());
+    }
+
+    #[test]
+    fn build_project_strict_rejects_type_conflicts() {
+        let path = get_temp_dir("test_build_project_strict_rejects_type_conflicts", true);
+        create_new_project(path.to_str().expect("Invalid path"), false).expect("Could not populate test directory");
+        create_file(path.join("defaults.json").as_path(), schema::get_sample()).expect("Could not create defaults file");
+        create_file(path.join("model.json").as_path(), r#"{"$extends": "defaults.json", "model_details": {"name": ["Model"]}}"#).expect("Could not create modelcard data file");
+        let result = build_project(&path, Some("model.json".to_string()), None, &BuildOptions { strict: true, ..Default::default() });
+        assert!(format!("{:?}", result.unwrap_err()).contains("/model_details/name: string"));
+        assert!(!path.join("cards/model.md").exists());
+    }
+
+    #[test]
+    fn build_project_denies_warnings() {
+        let path = get_temp_dir("test_build_project_denies_warnings", true);
+        create_new_project(path.to_str().expect("Invalid path"), false).expect("Could not populate test directory");
+        create_file(path.join("defaults.json").as_path(), schema::get_sample()).expect("Could not create defaults file");
+        create_file(path.join("model.json").as_path(), r#"{"$extends": "defaults.json", "model_details": {"overveiw": "Typo"}}"#).expect("Could not create modelcard data file");
+        build_project(&path, Some("model.json".to_string()), None, &BuildOptions::default()).expect("Warnings should not fail the build");
+        assert!(path.join("cards/model.md").exists());
+        let target = path.join("denied/model.md").to_string_lossy().to_string();
+        let options = BuildOptions { deny_warnings: true, ..Default::default() };
+        assert!(!build_project(&path, Some("model.json".to_string()), Some(target.clone()), &options).expect("Warnings should not be errors"));
+        let options = BuildOptions { strict: true, ..Default::default() };
+        assert!(!build_project(&path, Some("model.json".to_string()), Some(target), &options).expect("Warnings should not be errors"));
+        assert!(!path.join("denied/model.md").exists());
+    }
+
+    #[test]
+    fn build_project_fails_below_min_score() {
+        let path = get_temp_dir("test_build_project_fails_below_min_score", true);
+        create_new_project(path.to_str().expect("Invalid path"), false).expect("Could not populate test directory");
+        create_file(path.join("model.json").as_path(), r#"{"model_details": {"name": "Model"}}"#).expect("Could not create modelcard data file");
+        let result = build_project(&path, Some("model.json".to_string()), None, &BuildOptions { min_score: Some(50.0), ..Default::default() });
+        assert!(format!("{:?}", result.unwrap_err()).contains("complete, the minimum is 50%"));
+        assert!(!path.join("cards/model.md").exists());
+        build_project(&path, None, None, &BuildOptions { min_score: Some(50.0), ..Default::default() }).expect("Could not build complete project");
+    }
 }
@@ -1,11 +1,14 @@
 use std::path::Path;
-use modelcards::validate::check_against_schema;
+use modelcards::{merge::MergeOptions, validate::{check_report_with, Profile, ValidationReport}};
 use anyhow::Result;
 
-pub fn check_project(path: &Path, modelcard: Option<String>) -> Result<bool> {
+/// Check the modelcard of the project against the project schema with the profile layered over it and return all problems found.
+/// Schemas referenced by the project schema are also searched in the `schema_path` directories, relative to the project.
+/// With `strict` type conflicts between the data layers fail the check like in the build.
+pub fn check_project(path: &Path, modelcard: Option<String>, schema_path: &[String], profile: Profile, strict: bool) -> Result<ValidationReport> {
     let modelcard = modelcard.unwrap_or_else(|| "sample.json".to_string());
-    let modelcard = Path::new(&modelcard);
-    check_against_schema(path, modelcard)
+    let options = MergeOptions { strict, interpolate: true, ..Default::default() };
+    check_report_with(path, Path::new(&modelcard), schema_path, profile, &options)
 }
 
 #[cfg(test)]
@@ -46,6 +49,57 @@ mod tests {
     fn check_valid_against_schema() {
         let dir = get_temp_dir("test_check_against_schema", true);
         populate_modelcards_dir(&dir).expect("Could not populate modelcards directory");
-        assert!(check_against_schema(&dir, &dir.join("sample.json")).is_ok());
+        assert!(check_project(&dir, Some(dir.join("sample.json").to_string_lossy().to_string()), &[], Profile::Google, false).expect("Could not check project").is_valid());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn check_invalid_against_schema() {
+        let dir = get_temp_dir("test_check_invalid_against_schema", true);
+        populate_modelcards_dir(&dir).expect("Could not populate modelcards directory");
+        create_file(&dir.join("invalid.json"), r#"{"model_details": {"name": 1}}"#).expect("Could not create modelcard file");
+        let report = check_project(&dir, Some(dir.join("invalid.json").to_string_lossy().to_string()), &[], Profile::Google, false).expect("Could not check project");
+        assert_eq!(report.errors().count(), 1);
+        assert_eq!(report.entries[0].pointer, "/model_details/name");
+    }
+
+    #[test]
+    fn check_against_governance_profile() {
+        let dir = get_temp_dir("test_check_against_governance_profile", true);
+        populate_modelcards_dir(&dir).expect("Could not populate modelcards directory");
+        let sample = Some(dir.join("sample.json").to_string_lossy().to_string());
+        assert!(check_project(&dir, sample, &[], Profile::EuAiAct, false).expect("Could not check project").is_valid());
+
+        create_file(&dir.join("model.json"), r#"{"$extends": "sample.json", "quantitative_analysis": {"performance_metrics": [{"type": "accuracy", "value": "0.9"}]}}"#).expect("Could not create modelcard file");
+        let report = check_project(&dir, Some(dir.join("model.json").to_string_lossy().to_string()), &[], Profile::EuAiAct, false).expect("Could not check project");
+        assert!(!report.is_valid());
+        assert!(report.errors().any(|entry| entry.keyword.starts_with("eu-ai-act-")), "{}", report);
+        assert!(report.entries.iter().all(|entry| entry.location.is_some()), "{}", report);
+    }
+
+    #[test]
+    fn check_strict_rejects_type_conflicts() {
+        let dir = get_temp_dir("test_check_strict_rejects_type_conflicts", true);
+        populate_modelcards_dir(&dir).expect("Could not populate modelcards directory");
+        create_file(&dir.join("model.json"), r#"{"$extends": "sample.json", "model_details": {"owners": "Jane"}}"#).expect("Could not create modelcard file");
+        let modelcard = Some(dir.join("model.json").to_string_lossy().to_string());
+        assert!(check_project(&dir, modelcard.clone(), &[], Profile::Google, false).is_ok());
+        let error = check_project(&dir, modelcard, &[], Profile::Google, true).unwrap_err();
+        assert!(format!("{:?}", error).contains("/model_details/owners"), "{:?}", error);
+    }
+
+    #[test]
+    fn check_split_schema_from_search_path() {
+        let dir = get_temp_dir("test_check_split_schema_from_search_path", true);
+        create_dir(dir.join("schema")).expect("Could not create schema directory");
+        create_dir(dir.join("shared")).expect("Could not create search directory");
+        create_file(&dir.join("schema/modelcard.schema.json"), r#"{"type": "object", "properties": {"owner": {"$ref": "https://example.org/schemas/owner.json"}}}"#).expect("Could not create schema file");
+        create_file(&dir.join("shared/owner.json"), r#"{"$id": "https://example.org/schemas/owner.json", "type": "object", "required": ["name"]}"#).expect("Could not create referenced schema file");
+        create_file(&dir.join("model.json"), r#"{"owner": {}}"#).expect("Could not create modelcard file");
+        let modelcard = Some(dir.join("model.json").to_string_lossy().to_string());
+
+        assert!(check_project(&dir, modelcard.clone(), &[], Profile::Google, false).is_err());
+        let report = check_project(&dir, modelcard, &["shared".to_string()], Profile::Google, false).expect("Could not check project");
+        assert_eq!(report.errors().map(|entry| entry.pointer.as_str()).collect::<Vec<_>>(), vec!["/owner"]);
+        remove_dir_all(&dir).expect("Failed to remove test directory");
+    }
+}
@@ -0,0 +1,58 @@
+use modelcards::{
+    lint::{lint, LintConfig},
+    merge::{from_paths_with_provenance, MergeOptions},
+    validate::ValidationReport
+};
+use anyhow::{Context, Result};
+
+/// Merge the sources and check the result against the lint rules, returning all findings located in the source files
+pub fn lint_modelcard(sources: Vec<String>, config: &LintConfig, options: &MergeOptions) -> Result<ValidationReport> {
+    let (modelcard, provenance) = from_paths_with_provenance(sources, options)
+        .context("Could not construct modelcard source data")?;
+    let mut report = lint(&modelcard, config)?;
+    report.locate(&provenance);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use modelcards::{lint::RuleLevel, utils::create_file, validate::Severity};
+    use std::{
+        env::temp_dir,
+        fs::{create_dir, remove_dir_all},
+        path::PathBuf
+    };
+
+    fn get_temp_dir(path: &str, create: bool) -> PathBuf {
+        let mut dir = temp_dir();
+        dir.push(path);
+        if dir.exists() {
+            remove_dir_all(&dir).expect("Could not free test directory");
+        }
+        if create {
+            create_dir(&dir).expect("Could not create test directory");
+        }
+        dir
+    }
+
+    #[test]
+    fn test_lint_modelcard_locates_findings() -> Result<()> {
+        let temp_dir = get_temp_dir("mc_lint_locates_findings", true);
+        let modelcard_path = temp_dir.join("modelcard.json");
+        create_file(&modelcard_path, "{\n  \"model_details\": {\n    \"owners\": [\n      {\"name\": \"Team\"}\n    ]\n  }\n}\n")?;
+
+        let sources = vec![modelcard_path.to_string_lossy().to_string()];
+        let report = lint_modelcard(sources.clone(), &LintConfig::default(), &MergeOptions::default())?;
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].severity, Severity::Warning);
+        assert_eq!(report.entries[0].location.as_ref().and_then(|location| location.line), Some(4));
+        assert!(report.is_valid());
+
+        let mut config = LintConfig::default();
+        config.severity.insert("owner-contact".to_string(), RuleLevel::Error);
+        let report = lint_modelcard(sources, &config, &MergeOptions::default())?;
+        assert!(!report.is_valid());
+        Ok(())
+    }
+}
@@ -1,32 +1,117 @@
 use std::fs;
-use anyhow::{bail, Result};
+use std::path::Path;
+use modelcards::{
+    merge::{leaves, three_way, Conflict, MergeMode, MergeOptions},
+    patch::is_patch_file,
+    utils::{parse_data, read_source, write_target, DataFormat, STDIO}
+};
+use serde_json::Value;
+use anyhow::{bail, Context, Result};
 
-pub fn merge_modelcards(sources: Vec<String>, target: Option<String>) -> Result<String> {
+/// Build the merge options from the merge related command line arguments
+pub fn merge_options(mode: MergeMode, format: Option<DataFormat>, numeric_prefixes: bool, strict: bool, arrays: &[String]) -> Result<MergeOptions> {
+    let mut options = MergeOptions { mode, format, numeric_prefixes, strict, ..Default::default() };
+    for spec in arrays {
+        options.add_array_strategy(spec)?;
+    }
+    Ok(options)
+}
+
+/// Merge the modelcards and explain for every value which source file set it and which values it shadowed.
+/// The sources are read once, so stdin can be explained as well; returns the merged modelcard and the explanation.
+pub fn explain_modelcards(sources: Vec<String>, target: Option<String>, options: MergeOptions) -> Result<(String, String)> {
     if sources.is_empty() {
         bail!("No modelcards to merge");
     }
-    let merged = if sources.len() < 2 {
-        fs::read_to_string(&sources[0])?
-    } else {
-        let json_result = modelcards::merge::from_paths(sources)?;
-        if json_result.is_object() {
-            serde_json::to_string_pretty(&json_result)?
-        } else {
-            json_result.to_string()
+    let (merged, provenance) = modelcards::merge::from_paths_with_provenance(sources, &options)?;
+    let result = serde_json::to_string_pretty(&merged)?;
+    if let Some(target_path) = target {
+        write_target(&target_path, &result)?;
+    }
+    let mut lines = vec![];
+    for (pointer, value) in leaves(&merged) {
+        lines.push(format!("{} = {}", pointer, shorten(value)));
+        if let Some(origin) = provenance.get(&pointer) {
+            lines.push(format!("    set by {}", origin.source));
+            for shadowed in origin.shadowed.iter().rev() {
+                lines.push(format!("    shadows {} = {}", shadowed.source, shorten(&shadowed.value)));
+            }
         }
+    }
+    Ok((result, lines.join("\n")))
+}
+
+/// Maximum number of characters of a value shown in the explanation (e.g. to hide base64 images)
+const EXPLAIN_VALUE_WIDTH: usize = 80;
+
+fn shorten(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() > EXPLAIN_VALUE_WIDTH {
+        format!("{}...", text.chars().take(EXPLAIN_VALUE_WIDTH).collect::<String>())
+    } else {
+        text
+    }
+}
+
+pub fn merge_modelcards(sources: Vec<String>, target: Option<String>, options: MergeOptions) -> Result<String> {
+    if sources.is_empty() {
+        bail!("No modelcards to merge");
+    }
+    let single_json = sources.len() < 2
+        && Path::new(&sources[0]).is_file()
+        && !is_patch_file(&sources[0])
+        && options.format.or_else(|| DataFormat::from_path(Path::new(&sources[0]))).unwrap_or_default() == DataFormat::Json;
+    let content = if single_json { Some(fs::read_to_string(&sources[0])?) } else { None };
+    let json_result = modelcards::merge::from_paths_with(sources, &options)?;
+    let merged = match content {
+        // a single JSON file left unchanged by merging (no parents, embedded files or references) keeps its formatting
+        Some(content) if serde_json::from_str::<Value>(&content).is_ok_and(|value| value == json_result) => content,
+        _ if json_result.is_object() => serde_json::to_string_pretty(&json_result)?,
+        _ => json_result.to_string(),
     };
     if let Some(target_path) = target {
-        fs::write(target_path, &merged)?;
+        write_target(&target_path, &merged)?;
     }
     Ok(merged)
 }
 
+/// Merge the changes of two modelcards (ours and theirs) relative to their common base
+/// 
+/// The files are compared as written, without resolving `$extends`, `$file` or `${...}` references, so the merged
+/// modelcard keeps them. It is written to the target even if there are conflicts, so conflict markers can be resolved by hand.
+/// One of the three files can be read from stdin (`-`).
+pub fn merge_three_way(base: String, sources: Vec<String>, target: Option<String>, options: &MergeOptions, markers: bool) -> Result<(String, Vec<Conflict>)> {
+    let (ours, theirs) = match sources.as_slice() {
+        [ours, theirs] => (ours.clone(), theirs.clone()),
+        _ => bail!("A three-way merge needs exactly two sources (ours and theirs), found {}", sources.len()),
+    };
+    if [&base, &ours, &theirs].iter().filter(|source| source.as_str() == STDIO).count() > 1 {
+        bail!("Only one of base, ours and theirs can be read from stdin");
+    }
+    let base = load_source(&base, options.format)?;
+    let ours = load_source(&ours, options.format)?;
+    let theirs = load_source(&theirs, options.format)?;
+    let (merged, conflicts) = three_way(&base, &ours, &theirs, markers);
+    let merged = serde_json::to_string_pretty(&merged)?;
+    if let Some(target_path) = target {
+        write_target(&target_path, &merged)?;
+    }
+    Ok((merged, conflicts))
+}
+
+/// Load a single data file as written, or stdin for `-`
+fn load_source(source: &str, format: Option<DataFormat>) -> Result<Value> {
+    let content = read_source(source)?;
+    let format = format.or_else(|| DataFormat::from_path(Path::new(source))).unwrap_or_default();
+    parse_data(&content, format).with_context(|| format!("Invalid {} in source '{}'", format, source))
+}
+
 /// Test if the merge_modelcards function works
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs::{create_dir, remove_dir_all};
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use std::env::temp_dir;
     use modelcards::utils::create_file;
 
@@ -47,7 +132,7 @@ mod tests {
         let path = get_temp_dir("test_merge_modelcards_to_file", true);
         create_file(path.join("modelcard1.json").as_path(), r#"{"name": "model1"}"#).expect("Could not create modelcard1 data file");
         create_file(path.join("modelcard2.json").as_path(), r#"{"name": "model2"}"#).expect("Could not create modelcard2 data file");
-        merge_modelcards(vec![path.join("modelcard1.json").to_str().expect("Invalid path").to_string(), path.join("modelcard2.json").to_str().expect("Invalid path").to_string()], Some(path.join("merged.json").to_str().expect("Invalid path").to_string())).expect("Could not merge modelcards");
+        merge_modelcards(vec![path.join("modelcard1.json").to_str().expect("Invalid path").to_string(), path.join("modelcard2.json").to_str().expect("Invalid path").to_string()], Some(path.join("merged.json").to_str().expect("Invalid path").to_string()), MergeOptions::default()).expect("Could not merge modelcards");
         assert!(path.join("merged.json").exists());
     }
 
@@ -56,7 +141,7 @@ mod tests {
         let path = get_temp_dir("test_merge_modelcards_to_stdout", true);
         create_file(path.join("modelcard1.json").as_path(), r#"{"name": "model1"}"#).expect("Could not create modelcard1 data file");
         create_file(path.join("modelcard2.json").as_path(), r#"{"name": "model2"}"#).expect("Could not create modelcard2 data file");
-        let merged = merge_modelcards(vec![path.join("modelcard1.json").to_str().expect("Invalid path").to_string(), path.join("modelcard2.json").to_str().expect("Invalid path").to_string()], None).expect("Could not merge modelcards");
+        let merged = merge_modelcards(vec![path.join("modelcard1.json").to_str().expect("Invalid path").to_string(), path.join("modelcard2.json").to_str().expect("Invalid path").to_string()], None, MergeOptions::default()).expect("Could not merge modelcards");
         println!("{}", merged);
     }
 
@@ -64,7 +149,7 @@ mod tests {
     fn merge_single_modelcard_to_file() {
         let path = get_temp_dir("test_merge_single_modelcard_to_file", true);
         create_file(path.join("modelcard.json").as_path(), r#"{"name": "single_model"}"#).expect("Could not create modelcard data file");
-        merge_modelcards(vec![path.join("modelcard.json").to_str().expect("Invalid path").to_string()], Some(path.join("merged_single.json").to_str().expect("Invalid path").to_string())).expect("Could not merge single modelcard");
+        merge_modelcards(vec![path.join("modelcard.json").to_str().expect("Invalid path").to_string()], Some(path.join("merged_single.json").to_str().expect("Invalid path").to_string()), MergeOptions::default()).expect("Could not merge single modelcard");
         assert!(path.join("merged_single.json").exists());
         let merged_content = fs::read_to_string(path.join("merged_single.json")).expect("Could not read merged file");
         assert_eq!(merged_content, r#"{"name": "single_model"}"#);
@@ -72,7 +157,7 @@ mod tests {
 
     #[test]
     fn merge_modelcards_empty_sources() {
-        let result = merge_modelcards(vec![], None);
+        let result = merge_modelcards(vec![], None, MergeOptions::default());
         assert!(result.is_err());
     }
 
@@ -80,17 +165,150 @@ mod tests {
     fn merge_modelcards_invalid_path() {
         let path = get_temp_dir("test_merge_modelcards_invalid_path", true);
         let invalid_path = path.join("non_existent_modelcard.json").to_str().expect("Invalid path").to_string();
-        let result = merge_modelcards(vec![invalid_path], None);
+        let result = merge_modelcards(vec![invalid_path], None, MergeOptions::default());
         assert!(result.is_err());
     }
 
+    #[test]
+    fn merge_modelcards_with_array_strategy() {
+        let path = get_temp_dir("test_merge_modelcards_with_array_strategy", true);
+        create_file(path.join("defaults.json").as_path(), r#"{"limitations": ["slow"], "tags": ["a"]}"#).expect("Could not create defaults data file");
+        create_file(path.join("model.json").as_path(), r#"{"limitations": ["large"], "tags": ["b"]}"#).expect("Could not create model data file");
+        let sources = vec![path.join("defaults.json").to_str().expect("Invalid path").to_string(), path.join("model.json").to_str().expect("Invalid path").to_string()];
+        let options = merge_options(MergeMode::Deep, None, false, false, &["/limitations=append".to_string()]).expect("Invalid merge options");
+        let merged = merge_modelcards(sources, None, options).expect("Could not merge modelcards");
+        let merged: serde_json::Value = serde_json::from_str(&merged).expect("Invalid merged json");
+        assert_eq!(merged, serde_json::json!({"limitations": ["slow", "large"], "tags": ["b"]}));
+    }
+
+    #[test]
+    fn merge_modelcards_with_invalid_array_strategy() {
+        let result = merge_options(MergeMode::Deep, None, false, false, &["shuffle".to_string()]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn explain_modelcards_lists_sources() {
+        let path = get_temp_dir("test_explain_modelcards_lists_sources", true);
+        create_file(path.join("defaults.json").as_path(), r#"{"name": "default", "license": "MIT"}"#).expect("Could not create defaults data file");
+        create_file(path.join("model.json").as_path(), r#"{"name": "model"}"#).expect("Could not create model data file");
+        let defaults = path.join("defaults.json").to_str().expect("Invalid path").to_string();
+        let model = path.join("model.json").to_str().expect("Invalid path").to_string();
+        let target = path.join("merged.json").to_str().expect("Invalid path").to_string();
+        let (merged, explanation) = explain_modelcards(vec![defaults.clone(), model.clone()], Some(target), MergeOptions::default()).expect("Could not explain modelcards");
+        assert_eq!(fs::read_to_string(path.join("merged.json")).expect("Could not read merged file"), merged);
+        let expected = format!("/license = \"MIT\"\n    set by {defaults}\n/name = \"model\"\n    set by {model}\n    shadows {defaults} = \"default\"");
+        assert_eq!(explanation, expected);
+    }
+
+    #[test]
+    fn merge_single_yaml_modelcard_to_json() {
+        let path = get_temp_dir("test_merge_single_yaml_modelcard_to_json", true);
+        create_file(path.join("modelcard.yaml").as_path(), "name: single_model\n").expect("Could not create modelcard data file");
+        let merged = merge_modelcards(vec![path.join("modelcard.yaml").to_str().expect("Invalid path").to_string()], None, MergeOptions::default()).expect("Could not merge single modelcard");
+        let merged: serde_json::Value = serde_json::from_str(&merged).expect("Invalid merged json");
+        assert_eq!(merged, serde_json::json!({"name": "single_model"}));
+    }
+
+    #[test]
+    fn merge_modelcards_from_directory() {
+        let path = get_temp_dir("test_merge_modelcards_from_directory", true);
+        create_dir(path.join("layers")).expect("Could not create layers directory");
+        create_file(path.join("layers/00-defaults.json").as_path(), r#"{"name": "default", "license": "MIT"}"#).expect("Could not create defaults data file");
+        create_file(path.join("layers/10-model.json").as_path(), r#"{"name": "model"}"#).expect("Could not create model data file");
+        let merged = merge_modelcards(vec![path.join("layers").to_str().expect("Invalid path").to_string()], None, MergeOptions::default()).expect("Could not merge modelcards");
+        let merged: serde_json::Value = serde_json::from_str(&merged).expect("Invalid merged json");
+        assert_eq!(merged, serde_json::json!({"name": "model", "license": "MIT"}));
+    }
+
+    #[test]
+    fn merge_single_modelcard_with_extends() {
+        let path = get_temp_dir("test_merge_single_modelcard_with_extends", true);
+        create_file(path.join("defaults.json").as_path(), r#"{"license": "MIT"}"#).expect("Could not create defaults data file");
+        create_file(path.join("model.json").as_path(), r#"{"$extends": "defaults.json", "name": "model"}"#).expect("Could not create model data file");
+        let merged = merge_modelcards(vec![path.join("model.json").to_str().expect("Invalid path").to_string()], None, MergeOptions::default()).expect("Could not merge modelcard");
+        let merged: serde_json::Value = serde_json::from_str(&merged).expect("Invalid merged json");
+        assert_eq!(merged, serde_json::json!({"license": "MIT", "name": "model"}));
+    }
+
+    #[test]
+    fn merge_single_modelcard_keeps_literal_references() {
+        let path = get_temp_dir("test_merge_single_modelcard_keeps_literal_references", true);
+        let content = r#"{"name": "model",   "price": "${HOME} costs $5"}"#;
+        create_file(path.join("modelcard.json").as_path(), content).expect("Could not create modelcard data file");
+        let source = path.join("modelcard.json").to_str().expect("Invalid path").to_string();
+        assert_eq!(merge_modelcards(vec![source.clone()], None, MergeOptions::default()).expect("Could not merge single modelcard"), content);
+        let options = MergeOptions { interpolate: true, ..Default::default() };
+        assert_eq!(merge_modelcards(vec![source], None, options).expect("Could not merge single modelcard"), content);
+    }
+
+    #[test]
+    fn merge_single_modelcard_as_patch() {
+        let path = get_temp_dir("test_merge_single_modelcard_as_patch", true);
+        create_file(path.join("modelcard.json").as_path(), r#"{"name": "single_model", "license": null}"#).expect("Could not create modelcard data file");
+        let options = MergeOptions { mode: MergeMode::Patch, ..Default::default() };
+        let merged = merge_modelcards(vec![path.join("modelcard.json").to_str().expect("Invalid path").to_string()], None, options).expect("Could not merge single modelcard");
+        let merged: serde_json::Value = serde_json::from_str(&merged).expect("Invalid merged json");
+        assert_eq!(merged, serde_json::json!({"name": "single_model"}));
+    }
+
+    #[test]
+    fn merge_modelcards_as_patch_removes_fields() {
+        let path = get_temp_dir("test_merge_modelcards_as_patch_removes_fields", true);
+        create_file(path.join("defaults.json").as_path(), r#"{"name": "default", "references": [{"reference": "a"}]}"#).expect("Could not create defaults data file");
+        create_file(path.join("model.json").as_path(), r#"{"name": "model", "references": null}"#).expect("Could not create model data file");
+        let sources = vec![path.join("defaults.json").to_str().expect("Invalid path").to_string(), path.join("model.json").to_str().expect("Invalid path").to_string()];
+        let options = MergeOptions { mode: MergeMode::Patch, ..Default::default() };
+        let merged = merge_modelcards(sources, None, options).expect("Could not merge modelcards");
+        let merged: serde_json::Value = serde_json::from_str(&merged).expect("Invalid merged json");
+        assert_eq!(merged, serde_json::json!({"name": "model"}));
+    }
+
     #[test]
     fn merge_modelcards_to_file_with_invalid_target() {
         let path = get_temp_dir("test_merge_modelcards_to_file_with_invalid_target", true);
         create_file(path.join("modelcard1.json").as_path(), r#"{"name": "model1"}"#).expect("Could not create modelcard1 data file");
         create_file(path.join("modelcard2.json").as_path(), r#"{"name": "model2"}"#).expect("Could not create modelcard2 data file");
-        let invalid_target = path.join("/invalid/path/merged.json").to_str().expect("Invalid path").to_string();
-        let result = merge_modelcards(vec![path.join("modelcard1.json").to_str().expect("Invalid path").to_string(), path.join("modelcard2.json").to_str().expect("Invalid path").to_string()], Some(invalid_target));
+        let invalid_target = Path::new("/invalid/path/merged.json").to_str().expect("Invalid path").to_string();
+        let result = merge_modelcards(vec![path.join("modelcard1.json").to_str().expect("Invalid path").to_string(), path.join("modelcard2.json").to_str().expect("Invalid path").to_string()], Some(invalid_target), MergeOptions::default());
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn merge_modelcards_three_way() {
+        let path = get_temp_dir("test_merge_modelcards_three_way", true);
+        create_file(path.join("base.json").as_path(), r#"{"description": "draft", "accuracy": 0.8, "name": "model"}"#).expect("Could not create base data file");
+        create_file(path.join("ours.json").as_path(), r#"{"description": "final", "accuracy": 0.8, "name": "our model"}"#).expect("Could not create ours data file");
+        create_file(path.join("theirs.yaml").as_path(), "description: draft\naccuracy: 0.9\nname: their model\n").expect("Could not create theirs data file");
+        let sources = vec![path.join("ours.json").to_str().expect("Invalid path").to_string(), path.join("theirs.yaml").to_str().expect("Invalid path").to_string()];
+        let target = path.join("merged.json").to_str().expect("Invalid path").to_string();
+        let base = path.join("base.json").to_str().expect("Invalid path").to_string();
+        let (merged, conflicts) = merge_three_way(base.clone(), sources.clone(), Some(target), &MergeOptions::default(), true).expect("Could not merge modelcards");
+        let merged: serde_json::Value = serde_json::from_str(&merged).expect("Invalid merged json");
+        assert_eq!(merged, serde_json::json!({
+            "description": "final",
+            "accuracy": 0.9,
+            "name": {"$conflict": {"base": "model", "ours": "our model", "theirs": "their model"}}
+        }));
+        assert_eq!(conflicts.len(), 1);
+        assert!(path.join("merged.json").exists());
+        assert!(merge_three_way(base.clone(), sources[..1].to_vec(), None, &MergeOptions::default(), false).is_err());
+        let error = merge_three_way(base, vec![STDIO.to_string(), STDIO.to_string()], None, &MergeOptions::default(), false).unwrap_err();
+        assert!(error.to_string().contains("Only one of base, ours and theirs"), "{}", error);
+    }
+
+    #[test]
+    fn merge_modelcards_three_way_keeps_references() {
+        let path = get_temp_dir("test_merge_modelcards_three_way_keeps_references", true);
+        create_file(path.join("defaults.json").as_path(), r#"{"license": "MIT"}"#).expect("Could not create defaults data file");
+        create_file(path.join("base.json").as_path(), r#"{"$extends": "defaults.json", "name": "model", "title": "${/name}"}"#).expect("Could not create base data file");
+        create_file(path.join("ours.json").as_path(), r#"{"$extends": "defaults.json", "name": "our model", "title": "${/name}"}"#).expect("Could not create ours data file");
+        create_file(path.join("theirs.json").as_path(), r#"{"$extends": "defaults.json", "name": "model", "title": "${/name}", "graphic": {"$file": "plot.png"}}"#).expect("Could not create theirs data file");
+        let sources = vec![path.join("ours.json").to_str().expect("Invalid path").to_string(), path.join("theirs.json").to_str().expect("Invalid path").to_string()];
+        let base = path.join("base.json").to_str().expect("Invalid path").to_string();
+        let (merged, conflicts) = merge_three_way(base, sources, None, &MergeOptions::default(), false).expect("Could not merge modelcards");
+        let merged: serde_json::Value = serde_json::from_str(&merged).expect("Invalid merged json");
+        assert_eq!(merged, serde_json::json!({"$extends": "defaults.json", "name": "our model", "title": "${/name}", "graphic": {"$file": "plot.png"}}));
+        assert!(conflicts.is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+use std::{collections::HashSet, fs::{self, create_dir_all}, path::{Path, PathBuf}};
+use modelcards::{
+    merge::expand_source,
+    migrate::{builtin_migrations, load_migrations, migrate, schema_version},
+    utils::{create_file, format_data, parse_data, DataFormat, STDIO}
+};
+use serde_json::Value;
+use anyhow::{bail, Context, Result};
+
+/// Options of a migration
+#[derive(Debug, Clone, Default)]
+pub struct MigrateOptions {
+    /// Directory to write the migrated files to instead of rewriting them in place
+    pub target: Option<String>,
+    /// Schema version to migrate to (defaults to the latest version)
+    pub to: Option<String>,
+    /// Schema version of files without `schema_version` (files without are skipped otherwise)
+    pub from: Option<String>,
+    /// File with custom migrations used instead of the built-in migrations of the Google schema
+    pub migrations: Option<String>,
+    /// Format of the source files (detected by file extension if not set)
+    pub format: Option<DataFormat>,
+    /// Sort files of directory and glob sources by numeric prefix
+    pub numeric_prefixes: bool,
+}
+
+/// Migrate every source file on its own to the target schema version, returning a summary line per file.
+/// Files are rewritten in their own format, unchanged files are only written to a target directory.
+pub fn migrate_modelcards(sources: Vec<String>, options: &MigrateOptions) -> Result<Vec<String>> {
+    if sources.is_empty() {
+        bail!("No modelcards to migrate");
+    }
+    let custom;
+    let migrations = match &options.migrations {
+        Some(file) => {
+            custom = load_migrations(Path::new(file))?;
+            &custom[..]
+        }
+        None => builtin_migrations(),
+    };
+    if let Some(dir) = &options.target {
+        create_dir_all(dir).with_context(|| format!("Could not create target directory '{}'", dir))?;
+    }
+
+    let mut summary = vec![];
+    let mut written = HashSet::new();
+    for source in &sources {
+        for file in expand_source(source, options.numeric_prefixes)? {
+            if file == STDIO {
+                bail!("Modelcards from stdin can not be migrated, as they can not be written back");
+            }
+            let path = Path::new(&file);
+            let format = options.format.or_else(|| DataFormat::from_path(path)).unwrap_or_default();
+            let content = fs::read_to_string(path).with_context(|| format!("Failed to read file {}", file))?;
+            let mut modelcard = parse_data(&content, format).with_context(|| format!("Invalid {} in file {}", format, file))?;
+
+            let version = match (schema_version(&modelcard), &options.from) {
+                (Some(version), _) => version,
+                (None, Some(from)) => {
+                    if let Value::Object(fields) = &mut modelcard {
+                        fields.insert("schema_version".to_string(), Value::String(from.clone()));
+                    }
+                    from.clone()
+                }
+                (None, None) => {
+                    summary.push(format!("{}: skipped, no schema_version", file));
+                    continue;
+                }
+            };
+            let applied = migrate(&mut modelcard, migrations, options.to.as_deref())
+                .with_context(|| format!("Could not migrate {}", file))?;
+            let target = match &options.target {
+                Some(dir) => {
+                    let target = PathBuf::from(dir).join(path.file_name().context("Invalid modelcard path")?);
+                    if !written.insert(target.clone()) {
+                        bail!("Several sources would be written to '{}'", target.display());
+                    }
+                    target
+                }
+                None => path.to_path_buf(),
+            };
+            if applied.is_empty() {
+                if options.target.is_some() {
+                    create_file(&target, &content)?;
+                }
+                summary.push(format!("{}: up to date ({})", file, version));
+                continue;
+            }
+            create_file(&target, &format_data(&modelcard, format).with_context(|| format!("Could not write {}", target.display()))?)?;
+            let versions: Vec<&str> = applied.iter().map(|migration| migration.to.as_str()).collect();
+            summary.push(format!("{}: {} -> {}", target.display(), version, versions.join(" -> ")));
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use modelcards::utils::load_json_file;
+    use std::{env::temp_dir, fs::{create_dir, remove_dir_all}};
+
+    fn get_temp_dir(path: &str, create: bool) -> PathBuf {
+        let mut dir = temp_dir();
+        dir.push(path);
+        if dir.exists() {
+            remove_dir_all(&dir).expect("Could not free test directory");
+        }
+        if create {
+            create_dir(&dir).expect("Could not create test directory");
+        }
+        dir
+    }
+
+    const OUTDATED: &str = "schema_version: 0.0.2\nmodel_details:\n  name: Model\n  license: MIT\n";
+
+    #[test]
+    fn migrate_modelcards_in_place() -> Result<()> {
+        let dir = get_temp_dir("mc_migrate_in_place", true);
+        create_file(&dir.join("model.yaml"), OUTDATED)?;
+        create_file(&dir.join("layer.json"), r#"{"model_details": {"version": {"name": "1.0"}}}"#)?;
+        create_file(&dir.join("sample.json"), modelcards::assets::schema::get_sample())?;
+
+        let summary = migrate_modelcards(vec![dir.to_string_lossy().to_string()], &MigrateOptions::default())?;
+        assert_eq!(summary.len(), 3);
+        assert!(summary[0].ends_with("layer.json: skipped, no schema_version"), "{:?}", summary);
+        assert!(summary[1].ends_with("model.yaml: 0.0.2 -> 2.0"), "{:?}", summary);
+        assert!(summary[2].ends_with("sample.json: up to date (2.0)"), "{:?}", summary);
+        let migrated = load_json_file(&dir.join("model.yaml"))?;
+        assert_eq!(migrated["schema_version"], "2.0");
+        assert_eq!(migrated["model_details"]["licenses"][0]["identifier"], "MIT");
+        assert_eq!(fs::read_to_string(dir.join("sample.json"))?, modelcards::assets::schema::get_sample());
+        remove_dir_all(&dir).expect("Failed to remove test directory");
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_modelcards_to_target_dir() -> Result<()> {
+        let dir = get_temp_dir("mc_migrate_to_target_dir", true);
+        let source = dir.join("model.json");
+        create_file(&source, r#"{"model_details": {"license": "MIT"}}"#)?;
+        let options = MigrateOptions {
+            target: Some(dir.join("migrated").to_string_lossy().to_string()),
+            from: Some("0.0.2".to_string()),
+            ..Default::default()
+        };
+        migrate_modelcards(vec![source.to_string_lossy().to_string()], &options)?;
+        assert_eq!(load_json_file(&source)?, serde_json::json!({"model_details": {"license": "MIT"}}));
+        let migrated = load_json_file(&dir.join("migrated/model.json"))?;
+        assert_eq!(migrated, serde_json::json!({"schema_version": "2.0", "model_details": {"licenses": [{"identifier": "MIT"}]}}));
+
+        let options = MigrateOptions { to: Some("3.0".to_string()), ..options };
+        assert!(migrate_modelcards(vec![source.to_string_lossy().to_string()], &options).is_err());
+        remove_dir_all(&dir).expect("Failed to remove test directory");
+        Ok(())
+    }
+}
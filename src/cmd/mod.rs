@@ -1,13 +1,19 @@
 mod init;
 mod build;
 mod check;
+mod lint;
 mod merge;
+mod migrate;
 mod render;
+mod score;
 mod validate;
 
 pub use self::init::create_new_project;
-pub use self::build::build_project;
+pub use self::build::{build_project, BuildOptions};
 pub use self::check::check_project;
-pub use self::merge::merge_modelcards;
-pub use self::validate::validate_modelcard;
-pub use self::render::render_modelcard;
\ No newline at end of file
+pub use self::lint::lint_modelcard;
+pub use self::merge::{explain_modelcards, merge_modelcards, merge_options, merge_three_way};
+pub use self::validate::{print_report, validate_modelcard};
+pub use self::migrate::{migrate_modelcards, MigrateOptions};
+pub use self::render::render_modelcard;
+pub use self::score::score_modelcard;
\ No newline at end of file
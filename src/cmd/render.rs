@@ -1,15 +1,24 @@
 use std::path::Path;
 
 use anyhow::{bail, Result};
-use modelcards::utils::console;
+use modelcards::{merge::{expand_source, MergeOptions}, utils::{console, write_target, STDIO}};
 
-pub fn render_modelcard(sources: Vec<String>, template_file: Option<String>) -> Result<bool> {
-    let file_name = sources.last().ok_or_else(|| anyhow::anyhow!("No sources provided"))?;
-    let file_name = Path::new(file_name);
-    let target_file = Path::new(file_name.file_name().ok_or_else(|| anyhow::anyhow!("Invalid file path"))?).with_extension("md");
-    console::info(format!("Rendering modelcard to {}.", target_file.to_string_lossy()).as_str());
+pub fn render_modelcard(sources: Vec<String>, template_file: Option<String>, target: Option<String>, options: &MergeOptions) -> Result<bool> {
+    let last_source = sources.last().ok_or_else(|| anyhow::anyhow!("No sources provided"))?;
+    let target_file = match target {
+        Some(target) => target,
+        // piped data is rendered to stdout
+        None if last_source == STDIO => STDIO.to_string(),
+        None => {
+            // directories and glob patterns are named after the last file they contain
+            let last_file = expand_source(last_source, options.numeric_prefixes)?.pop().unwrap_or_else(|| last_source.clone());
+            let file_name = Path::new(&last_file);
+            Path::new(file_name.file_name().ok_or_else(|| anyhow::anyhow!("Invalid file path"))?).with_extension("md").to_string_lossy().to_string()
+        }
+    };
+    console::info(format!("Rendering modelcard to {}.", target_file).as_str());
 
-    if let Ok(modelcard) = modelcards::merge::from_paths(sources) {
+    if let Ok(modelcard) = modelcards::merge::from_paths_with(sources, options) {
         let result: Result<String> = match template_file {
             None => modelcards::render::render_value_to_template(modelcard, None),
             Some(file) => {
@@ -18,7 +27,7 @@ pub fn render_modelcard(sources: Vec<String>, template_file: Option<String>) ->
             }
         };
         if let Ok(rendered) = result {
-            modelcards::utils::create_file(target_file.as_path(), &rendered)?;
+            write_target(&target_file, &rendered)?;
             return Ok(true);
         }
         bail!("Could not render template: {:?}", result.err());
@@ -0,0 +1,63 @@
+use std::path::Path;
+use modelcards::{
+    merge::MergeOptions,
+    utils::load_json_file,
+    validate::{score_against_schema, Score}
+};
+use anyhow::{Context, Result};
+
+/// Merge the sources and score the completeness of the result against the schema
+pub fn score_modelcard(sources: Vec<String>, schema_file: Option<String>, options: &MergeOptions) -> Result<Score> {
+    let modelcard = modelcards::merge::from_paths_with(sources, options)
+        .context("Could not construct modelcard source data")?;
+    let schema = match schema_file {
+        Some(file) => load_json_file(Path::new(&file))?,
+        None => serde_json::from_str(modelcards::assets::schema::get_schema())?
+    };
+    score_against_schema(&modelcard, Some(schema))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use modelcards::utils::create_file;
+    use std::{
+        env::temp_dir,
+        fs::{create_dir, remove_dir_all},
+        path::PathBuf
+    };
+
+    fn get_temp_dir(path: &str, create: bool) -> PathBuf {
+        let mut dir = temp_dir();
+        dir.push(path);
+        if dir.exists() {
+            remove_dir_all(&dir).expect("Could not free test directory");
+        }
+        if create {
+            create_dir(&dir).expect("Could not create test directory");
+        }
+        dir
+    }
+
+    #[test]
+    fn test_score_modelcard_merges_sources() -> Result<()> {
+        let temp_dir = get_temp_dir("mc_score_merges_sources", true);
+        let base_path = temp_dir.join("base.json");
+        let model_path = temp_dir.join("model.json");
+        create_file(&base_path, r#"{"model_details": {"name": "Model"}}"#)?;
+        create_file(&model_path, r#"{"model_details": {"overview": "A model"}}"#)?;
+
+        let sources = vec![base_path.to_string_lossy().to_string(), model_path.to_string_lossy().to_string()];
+        let score = score_modelcard(sources, None, &MergeOptions::default())?;
+
+        let details = score.sections.iter().find(|section| section.name == "model_details").expect("Missing section");
+        assert_eq!(details.filled, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_score_modelcard_fails_with_nonexistent_source() {
+        let sources = vec!["nonexistent_modelcard.json".to_string()];
+        assert!(score_modelcard(sources, None, &MergeOptions::default()).is_err());
+    }
+}
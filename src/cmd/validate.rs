@@ -1,20 +1,39 @@
-use std::path::Path;
-use modelcards::utils::load_json_file;
+use std::path::PathBuf;
+use modelcards::{
+    merge::MergeOptions,
+    utils::{write_target, STDIO},
+    validate::{Profile, ReportFormat, SchemaOptions, ValidationReport}
+};
 use anyhow::{bail, Result};
 
 
-pub fn validate_modelcard(sources: Vec<String>, schema_file: Option<String>) -> Result<bool> {
-    let result = modelcards::merge::from_paths(sources);
-    if let Ok(modelcard) = result {
-        let schema = match schema_file {
-            Some(file) => load_json_file(Path::new(&file))?,
-            None => serde_json::from_str(modelcards::assets::schema::get_schema())?
+/// Merge the sources and validate the result against the schema file or the schema of the profile
+/// with the profile layered over it, returning all problems found located in the source files. References of the schema file are
+/// resolved from its directory and the schema search path.
+pub fn validate_modelcard(sources: Vec<String>, schema_file: Option<String>, search_path: &[String], profile: Profile, options: &MergeOptions) -> Result<ValidationReport> {
+    let result = modelcards::merge::from_paths_with_provenance(sources, options);
+    if let Ok((modelcard, provenance)) = result {
+        let schema_options = SchemaOptions { file: schema_file.map(PathBuf::from), ..Default::default() }
+            .with_search_path(search_path);
+        let schema = match schema_options.file {
+            Some(_) => Some(schema_options.load()?),
+            None => None
         };
-        return modelcards::validate::validate_against_schema(modelcard, Some(schema));
+        let mut report = profile.validate(&modelcard, schema, &schema_options)?;
+        report.locate(&provenance);
+        return Ok(report);
     }
     bail!("Could not construct modelcard source data: {:?}", result.err());
 }
 
+/// Print the report to stdout in the given format, an empty text report prints nothing
+pub fn print_report(report: &ValidationReport, format: ReportFormat, artifact: Option<&str>) -> Result<()> {
+    if format == ReportFormat::Text && report.entries.is_empty() {
+        return Ok(());
+    }
+    write_target(STDIO, &report.render(format, artifact))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,12 +61,12 @@ mod tests {
         let temp_dir = get_temp_dir("mc_valid_data_no_schema", true);
         let modelcard_path = temp_dir.join("modelcard.json");
         let content = modelcards::assets::schema::get_sample();
-        create_file(&modelcard_path, &content)?;
+        create_file(&modelcard_path, content)?;
 
         let sources = vec![modelcard_path.to_str().expect("Invalid path").to_string()];
-        let result = validate_modelcard(sources, None)?;
+        let result = validate_modelcard(sources, None, &[], Profile::Google, &MergeOptions::default())?;
 
-        assert!(result);
+        assert!(result.is_valid());
         Ok(())
     }
 
@@ -58,7 +77,7 @@ mod tests {
         create_file(&modelcard_path, r#"{{"invalid": "data"}}"#)?;
 
         let sources = vec![modelcard_path.to_str().expect("Invalid path").to_string()];
-        let result = validate_modelcard(sources, None);
+        let result = validate_modelcard(sources, None, &[], Profile::Google, &MergeOptions::default());
 
         assert!(result.is_err());
         Ok(())
@@ -73,16 +92,65 @@ mod tests {
         create_file(&schema_path, r#"{"type": "object", "properties": {"name": {"type": "string"}, "description": {"type": "string"}}, "required": ["name", "description"]}"#).expect("Could not create schema file.");
 
         let sources = vec![modelcard_path.to_str().expect("Invalid path").to_string()];
-        let result = validate_modelcard(sources, Some(schema_path.to_str().unwrap().to_string()))?;
+        let result = validate_modelcard(sources, Some(schema_path.to_str().unwrap().to_string()), &[], Profile::Google, &MergeOptions::default())?;
+
+        assert!(result.is_valid());
+        Ok(())
+    }
 
-        assert!(result);
+    #[test]
+    fn test_validate_modelcard_reports_invalid_data() -> Result<()> {
+        let temp_dir = get_temp_dir("mc_validation_report_invalid_data", true);
+        let modelcard_path = temp_dir.join("modelcard.json");
+        create_file(&modelcard_path, r#"{"model_details": {"name": 1, "version": {"name": 2}}}"#)?;
+
+        let sources = vec![modelcard_path.to_str().expect("Invalid path").to_string()];
+        let report = validate_modelcard(sources, None, &[], Profile::Google, &MergeOptions::default())?;
+
+        let pointers: Vec<&str> = report.errors().map(|entry| entry.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/model_details/name", "/model_details/version/name"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_huggingface_readme() -> Result<()> {
+        let temp_dir = get_temp_dir("mc_validation_huggingface_readme", true);
+        let readme_path = temp_dir.join("README.md");
+        create_file(&readme_path, "---\nlicense: other\ntags: [audio]\n---\n\n# My Model\n")?;
+
+        let sources = vec![readme_path.to_string_lossy().to_string()];
+        let report = validate_modelcard(sources.clone(), None, &[], Profile::HuggingFace, &MergeOptions::default())?;
+        assert!(!report.is_valid());
+        assert!(report.errors().any(|entry| entry.message.contains("license_name")), "{}", report);
+
+        create_file(&readme_path, "---\nlicense: other\nlicense_name: my-license\nlicense_link: LICENSE\ntags: [audio]\n---\n\n# My Model\n")?;
+        assert!(validate_modelcard(sources, None, &[], Profile::HuggingFace, &MergeOptions::default())?.is_valid());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_modelcard_locates_errors_in_layers() -> Result<()> {
+        let temp_dir = get_temp_dir("mc_validation_locates_errors", true);
+        let base_path = temp_dir.join("base.json");
+        let layer_path = temp_dir.join("layer.json");
+        create_file(&base_path, "{\n  \"model_details\": {\n    \"name\": \"Model\"\n  }\n}\n")?;
+        create_file(&layer_path, "{\n  \"model_details\": {\n    \"version\": {\n      \"name\": 2\n    }\n  }\n}\n")?;
+
+        let sources = vec![base_path.to_string_lossy().to_string(), layer_path.to_string_lossy().to_string()];
+        let report = validate_modelcard(sources, None, &[], Profile::Google, &MergeOptions::default())?;
+
+        let entry = report.errors().next().expect("Expected an error");
+        assert_eq!(entry.pointer, "/model_details/version/name");
+        let location = entry.location.as_ref().expect("Expected a location");
+        assert_eq!(location.to_string(), format!("{}:4:7", layer_path.display()));
+        assert!(report.to_string().contains("4 |       \"name\": 2\n"), "{}", report);
         Ok(())
     }
 
     #[test]
     fn test_validate_modelcard_fails_with_nonexistent_source() {
         let sources = vec!["nonexistent_modelcard.json".to_string()];
-        let result = validate_modelcard(sources, None);
+        let result = validate_modelcard(sources, None, &[], Profile::Google, &MergeOptions::default());
 
         assert!(result.is_err());
     }
@@ -94,7 +162,7 @@ mod tests {
         create_file(&modelcard_path, r#"{"name": "Test Model", "description": "A test model for validation."}"#)?;
 
         let sources = vec![modelcard_path.to_str().expect("Invalid path").to_string()];
-        let result = validate_modelcard(sources, Some("nonexistent_schema.json".to_string()));
+        let result = validate_modelcard(sources, Some("nonexistent_schema.json".to_string()), &[], Profile::Google, &MergeOptions::default());
 
         assert!(result.is_err());
         Ok(())
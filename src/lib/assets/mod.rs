@@ -1,3 +1,4 @@
 pub mod schema;
+pub mod spdx;
 pub mod templates;
 pub mod config;
\ No newline at end of file
@@ -6,4 +6,22 @@ pub fn get_schema() -> &'static str {
 
 pub fn get_sample() -> &'static str {
     include_str!("google.sample.json")
-}
\ No newline at end of file
+}
+pub fn get_huggingface_schema() -> &'static str {
+    include_str!("huggingface.modelcard.schema.json")
+}
+
+/// Get a governance profile overlay by name (`eu-ai-act`, `nist-ai-rmf` or `internal`).
+pub fn get_profile(name: &str) -> Option<&'static str> {
+    match name {
+        "eu-ai-act" => Some(include_str!("eu-ai-act.profile.json")),
+        "nist-ai-rmf" => Some(include_str!("nist-ai-rmf.profile.json")),
+        "internal" => Some(include_str!("internal.profile.json")),
+        _ => None,
+    }
+}
+
+/// Get the migrations between the versions of the Google model card schema.
+pub fn get_migrations() -> &'static str {
+    include_str!("google.migrations.json")
+}
@@ -0,0 +1,13 @@
+//! The SPDX license list (`licenses.json` and `exceptions.json` of
+//! [spdx/license-list-data](https://github.com/spdx/license-list-data/tree/main/json)),
+//! with the version of the list as `licenseListVersion`.
+
+use std::include_str;
+
+pub fn get_licenses() -> &'static str {
+    include_str!("licenses.json")
+}
+
+pub fn get_exceptions() -> &'static str {
+    include_str!("exceptions.json")
+}
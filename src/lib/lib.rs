@@ -18,17 +18,29 @@
 //! 
 //! The functions are divided into modules:
 //! - `assets`: Contains the assets used by the library, like templates and schemas.
+//! - `lint`: Rule based checks of model cards beyond JSON Schema.
 //! - `merge`: Functions to merge multiple model data files.
+//! - `migrate`: Declarative migrations of model cards between schema versions.
+//! - `patch`: Functions to apply JSON Patch documents to model data.
 //! - `render`: Functions to render model cards using Jinja templates.
+//! - `spdx`: Offline validation of SPDX license expressions.
 //! - `utils`: Utility functions used by the library.
 //! - `validate`: Functions to validate modelcards against a schema file.
 
 /// Contains the assets used by the library, like templates and schemas.
 pub mod assets;
+/// Rule based checks of model cards beyond JSON Schema.
+pub mod lint;
 /// Functions to merge multiple model data files.
 pub mod merge;
+/// Declarative migrations of model cards between schema versions.
+pub mod migrate;
+/// Functions to apply JSON Patch documents to model data.
+pub mod patch;
 /// Functions to render model cards using Jinja templates.
 pub mod render;
+/// Offline validation of SPDX license expressions.
+pub mod spdx;
 /// Utility functions used by the library.
 pub mod utils;
 /// Functions to validate modelcards against a schema file.
@@ -0,0 +1,401 @@
+//! # Lint
+//!
+//! Rule based checks of model cards beyond JSON Schema.
+//!
+//! Many governance rules can not be expressed in JSON Schema, e.g. that every performance metric carries a
+//! confidence interval. A lint [`Rule`] selects values of the merged model card with a path pattern and
+//! requires fields to be filled in each of them. Findings are returned as [`ValidationReport`], so they can be
+//! rendered as text, JSON or SARIF just like schema errors.
+//!
+//! ## Functions
+//!
+//! The module provides the following functions:
+//!
+//! - `builtin_rules() -> Vec<Rule>` - The built-in rules for the Google model card structure.
+//! - `lint(modelcard: &Value, config: &LintConfig) -> Result<ValidationReport>` - Check a model card against the built-in and configured rules.
+//! - `check_rules(modelcard: &Value, rules: &[Rule]) -> Result<ValidationReport>` - Check a model card against the given rules only.
+//!
+//! ## Paths
+//!
+//! The path of a rule is a JSON pointer, where a `*` token matches every item of an array (or every value of
+//! an object), e.g. `/quantitative_analysis/performance_metrics/*` selects each performance metric.
+//! A field is filled if it is present and not `null`, an empty string, array or object.
+//!
+//! ## Configuration
+//!
+//! The severity of every rule can be changed to `error`, `warning` or `off`, and custom rules can be added
+//! (a custom rule with the id of a built-in rule replaces it). In `config.toml` this reads:
+//!
+//! ```toml
+//! [lint.severity]
+//! owner-contact = "off"
+//! metric-confidence-interval = "error"
+//!
+//! [[lint.rules]]
+//! id = "metric-slice"
+//! path = "/quantitative_analysis/performance_metrics/*"
+//! require = ["slice"]
+//! severity = "warning"
+//! message = "performance metric is not computed on a slice"
+//! ```
+//!
+//! ## Errors
+//!
+//! The functions will return an error if a rule is malformed or the configuration refers to an unknown rule.
+//! The anyhow crate is used for error handling.
+//!
+
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use serde_json::Value;
+use anyhow::{bail, Result};
+
+use crate::{
+    merge::escape_pointer_token,
+    validate::{Severity, ValidationEntry, ValidationReport}
+};
+
+/// Token of a rule path matching every item of an array or value of an object.
+const WILDCARD: &str = "*";
+
+/// Level of a lint rule.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    /// Findings make the model card invalid (default for custom rules).
+    #[default]
+    Error,
+    /// Findings are reported, but the model card stays valid.
+    Warning,
+    /// The rule is not checked.
+    Off,
+}
+
+impl RuleLevel {
+    /// Severity of the findings, `None` if the rule is off.
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            RuleLevel::Error => Some(Severity::Error),
+            RuleLevel::Warning => Some(Severity::Warning),
+            RuleLevel::Off => None,
+        }
+    }
+}
+
+/// A lint rule requiring fields to be filled in the values selected by a path.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Rule {
+    /// Unique id of the rule, reported as keyword of its findings.
+    pub id: String,
+    /// JSON pointer of the checked values, `*` matches every item of an array.
+    pub path: String,
+    /// Fields which must all be filled.
+    #[serde(default)]
+    pub require: Vec<String>,
+    /// Fields of which at least one must be filled.
+    #[serde(default)]
+    pub require_any: Vec<String>,
+    /// Level of the rule.
+    #[serde(default)]
+    pub severity: RuleLevel,
+    /// Description of a finding, the missing fields are appended.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Lint configuration, as read from the `[lint]` section of `config.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct LintConfig {
+    /// Level per rule id, overriding the level of built-in and custom rules.
+    #[serde(default)]
+    pub severity: BTreeMap<String, RuleLevel>,
+    /// Custom rules, checked in addition to the built-in rules.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+fn rule(id: &str, path: &str, require: &[&str], require_any: &[&str], severity: RuleLevel, message: &str) -> Rule {
+    Rule {
+        id: id.to_string(),
+        path: path.to_string(),
+        require: require.iter().map(|field| field.to_string()).collect(),
+        require_any: require_any.iter().map(|field| field.to_string()).collect(),
+        severity,
+        message: Some(message.to_string()),
+    }
+}
+
+/// The built-in rules for the Google model card structure.
+///
+/// | Rule | Level | Checks |
+/// |------|-------|--------|
+/// | `metric-confidence-interval` | warning | every performance metric has a `confidence_interval` |
+/// | `risk-mitigation` | error | every risk has a `mitigation_strategy` |
+/// | `dataset-link-or-sensitive` | error | every dataset has a `link` or a `sensitive` flag |
+/// | `owner-contact` | warning | every owner has a `contact` |
+/// | `limitation-description` | warning | every limitation has a `description` |
+pub fn builtin_rules() -> Vec<Rule> {
+    vec![
+        rule("metric-confidence-interval", "/quantitative_analysis/performance_metrics/*", &["confidence_interval"], &[],
+            RuleLevel::Warning, "performance metric has no confidence interval"),
+        rule("risk-mitigation", "/considerations/ethical_considerations/*", &["mitigation_strategy"], &[],
+            RuleLevel::Error, "risk has no mitigation strategy"),
+        rule("dataset-link-or-sensitive", "/model_parameters/data/*", &[], &["link", "sensitive"],
+            RuleLevel::Error, "dataset has neither a link nor a sensitive flag"),
+        rule("owner-contact", "/model_details/owners/*", &["contact"], &[],
+            RuleLevel::Warning, "owner has no contact"),
+        rule("limitation-description", "/considerations/limitations/*", &["description"], &[],
+            RuleLevel::Warning, "limitation has no description"),
+    ]
+}
+
+/// Check a model card against the built-in and configured rules.
+///
+/// Every value selected by a rule path that misses a required field yields one entry with the rule id as keyword.
+///
+/// ## Errors
+///
+/// The function will return an error if a rule has no path starting with `/` or no required fields,
+/// or if the configuration sets the level of an unknown rule.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::{lint::{lint, LintConfig}, validate::Severity};
+///
+/// let modelcard = json!({"quantitative_analysis": {"performance_metrics": [
+///     {"type": "accuracy", "value": 0.9, "confidence_interval": {"lower_bound": 0.88, "upper_bound": 0.92}},
+///     {"type": "recall", "value": 0.8}
+/// ]}});
+/// let report = lint(&modelcard, &LintConfig::default()).unwrap();
+/// assert_eq!(report.entries.len(), 1);
+/// assert_eq!(report.entries[0].keyword, "metric-confidence-interval");
+/// assert_eq!(report.entries[0].pointer, "/quantitative_analysis/performance_metrics/1");
+/// assert_eq!(report.entries[0].severity, Severity::Warning);
+/// ```
+///
+pub fn lint(modelcard: &Value, config: &LintConfig) -> Result<ValidationReport> {
+    let mut rules = builtin_rules();
+    for custom in &config.rules {
+        match rules.iter_mut().find(|rule| rule.id == custom.id) {
+            Some(rule) => *rule = custom.clone(),
+            None => rules.push(custom.clone()),
+        }
+    }
+    for (id, level) in &config.severity {
+        match rules.iter_mut().find(|rule| &rule.id == id) {
+            Some(rule) => rule.severity = *level,
+            None => bail!("Unknown lint rule '{}'", id),
+        }
+    }
+    check_rules(modelcard, &rules)
+}
+
+/// Check a model card against the given rules only, e.g. the rules of a governance
+/// [`Profile`](crate::validate::Profile).
+///
+/// ## Errors
+///
+/// The function will return an error if a rule has no path starting with `/` or no required fields.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::lint::{check_rules, Rule};
+///
+/// let rule = Rule { id: "owner-name".to_string(), path: "/model_details/owners/*".to_string(), require: vec!["name".to_string()], ..Default::default() };
+/// let report = check_rules(&json!({"model_details": {"owners": [{"contact": "jane@example.com"}]}}), &[rule]).unwrap();
+/// assert_eq!(report.entries[0].pointer, "/model_details/owners/0");
+/// assert_eq!(report.entries[0].message, "missing 'name'");
+/// ```
+///
+pub fn check_rules(modelcard: &Value, rules: &[Rule]) -> Result<ValidationReport> {
+    let mut report = ValidationReport::default();
+    for rule in rules {
+        if !rule.path.starts_with('/') && !rule.path.is_empty() {
+            bail!("Path of lint rule '{}' must be a JSON pointer starting with '/'", rule.id);
+        }
+        if rule.require.is_empty() && rule.require_any.is_empty() {
+            bail!("Lint rule '{}' requires no fields", rule.id);
+        }
+        let severity = match rule.severity.severity() {
+            Some(severity) => severity,
+            None => continue,
+        };
+        for (pointer, value) in select(modelcard, &rule.path) {
+            if !value.is_object() {
+                continue;
+            }
+            let missing: Vec<String> = rule.require.iter()
+                .filter(|field| !is_filled(value.get(field.as_str())))
+                .map(|field| format!("'{}'", field))
+                .collect();
+            let any_missing = !rule.require_any.is_empty() && !rule.require_any.iter().any(|field| is_filled(value.get(field.as_str())));
+            let mut groups = vec![];
+            if !missing.is_empty() {
+                groups.push(missing.join(", "));
+            }
+            if any_missing {
+                let any = rule.require_any.iter().map(|field| format!("'{}'", field)).collect::<Vec<String>>().join(" or ");
+                groups.push(if groups.is_empty() { any } else { format!("one of {}", any) });
+            }
+            if groups.is_empty() {
+                continue;
+            }
+            let fields = groups.join("; ");
+            let message = match &rule.message {
+                Some(message) => format!("{} (missing {})", message, fields),
+                None => format!("missing {}", fields),
+            };
+            report.entries.push(ValidationEntry {
+                pointer,
+                keyword: rule.id.clone(),
+                severity,
+                message,
+                ..Default::default()
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Select all values matching a path with `*` wildcards, with their JSON pointers.
+fn select<'a>(value: &'a Value, path: &str) -> Vec<(String, &'a Value)> {
+    let mut selected = vec![(String::new(), value)];
+    for token in path.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        selected = selected.into_iter()
+            .flat_map(|(pointer, value)| -> Vec<(String, &Value)> {
+                match (value, token.as_str()) {
+                    (Value::Array(items), WILDCARD) => items.iter().enumerate()
+                        .map(|(index, item)| (format!("{}/{}", pointer, index), item))
+                        .collect(),
+                    (Value::Object(map), WILDCARD) => map.iter()
+                        .map(|(key, item)| (format!("{}/{}", pointer, escape_pointer_token(key)), item))
+                        .collect(),
+                    (Value::Array(items), index) => index.parse::<usize>().ok()
+                        .and_then(|index| items.get(index))
+                        .map(|item| vec![(format!("{}/{}", pointer, index), item)])
+                        .unwrap_or_default(),
+                    (Value::Object(map), key) => map.get(key)
+                        .map(|item| vec![(format!("{}/{}", pointer, escape_pointer_token(key)), item)])
+                        .unwrap_or_default(),
+                    _ => vec![],
+                }
+            })
+            .collect();
+    }
+    selected
+}
+
+fn is_filled(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::String(s)) => !s.trim().is_empty(),
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(Value::Object(map)) => !map.is_empty(),
+        Some(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn modelcard() -> Value {
+        json!({
+            "model_details": {"owners": [{"name": "Jane", "contact": "jane@example.com"}, {"name": "Team"}]},
+            "model_parameters": {"data": [{"name": "train", "link": "https://example.com"}, {"name": "eval"}, {"name": "private", "sensitive": {"sensitive_data": ["PII"]}}]},
+            "considerations": {"ethical_considerations": [{"name": "Bias", "mitigation_strategy": ""}]}
+        })
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let report = lint(&modelcard(), &LintConfig::default()).unwrap();
+        let findings: Vec<(&str, &str, Severity)> = report.entries.iter()
+            .map(|entry| (entry.keyword.as_str(), entry.pointer.as_str(), entry.severity))
+            .collect();
+        assert_eq!(findings, vec![
+            ("risk-mitigation", "/considerations/ethical_considerations/0", Severity::Error),
+            ("dataset-link-or-sensitive", "/model_parameters/data/1", Severity::Error),
+            ("owner-contact", "/model_details/owners/1", Severity::Warning),
+        ]);
+        assert_eq!(report.entries[1].message, "dataset has neither a link nor a sensitive flag (missing 'link' or 'sensitive')");
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn test_configured_severity_and_custom_rules() {
+        let config = LintConfig {
+            severity: BTreeMap::from([
+                ("risk-mitigation".to_string(), RuleLevel::Off),
+                ("dataset-link-or-sensitive".to_string(), RuleLevel::Warning),
+            ]),
+            rules: vec![
+                Rule { id: "owner-contact".to_string(), path: "/model_details/owners/*".to_string(), require: vec!["contact".to_string()], ..Default::default() },
+                Rule { id: "owner-role".to_string(), path: "/model_details/owners/0".to_string(), require: vec!["role".to_string(), "name".to_string()], ..Default::default() },
+            ],
+        };
+        let report = lint(&modelcard(), &config).unwrap();
+        let findings: Vec<(&str, &str, Severity)> = report.entries.iter()
+            .map(|entry| (entry.keyword.as_str(), entry.pointer.as_str(), entry.severity))
+            .collect();
+        assert_eq!(findings, vec![
+            ("dataset-link-or-sensitive", "/model_parameters/data/1", Severity::Warning),
+            ("owner-contact", "/model_details/owners/1", Severity::Error),
+            ("owner-role", "/model_details/owners/0", Severity::Error),
+        ]);
+        assert_eq!(report.entries[2].message, "missing 'role'");
+    }
+
+    #[test]
+    fn test_required_and_any_fields() {
+        let rule = Rule {
+            id: "owner".to_string(),
+            path: "/model_details/owners/*".to_string(),
+            require: vec!["role".to_string(), "name".to_string()],
+            require_any: vec!["contact".to_string(), "email".to_string()],
+            message: Some("owner is incomplete".to_string()),
+            ..Default::default()
+        };
+        let modelcard = json!({"model_details": {"owners": [{"name": "Jane", "contact": "jane@example.com"}, {"name": "Team"}, {}]}});
+        let report = check_rules(&modelcard, &[rule]).unwrap();
+        let messages: Vec<&str> = report.entries.iter().map(|entry| entry.message.as_str()).collect();
+        assert_eq!(messages, vec![
+            "owner is incomplete (missing 'role')",
+            "owner is incomplete (missing 'role'; one of 'contact' or 'email')",
+            "owner is incomplete (missing 'role', 'name'; one of 'contact' or 'email')",
+        ]);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let unknown = LintConfig { severity: BTreeMap::from([("unknown".to_string(), RuleLevel::Off)]), ..Default::default() };
+        assert!(lint(&modelcard(), &unknown).is_err());
+        let empty = LintConfig { rules: vec![Rule { id: "empty".to_string(), path: "/model_details".to_string(), ..Default::default() }], ..Default::default() };
+        assert!(lint(&modelcard(), &empty).is_err());
+        let relative = LintConfig { rules: vec![Rule { id: "relative".to_string(), path: "model_details".to_string(), require: vec!["name".to_string()], ..Default::default() }], ..Default::default() };
+        assert!(lint(&modelcard(), &relative).is_err());
+    }
+
+    #[test]
+    fn test_config_from_toml() {
+        let config: LintConfig = toml::from_str(r#"
+            [severity]
+            owner-contact = "off"
+
+            [[rules]]
+            id = "metric-slice"
+            path = "/quantitative_analysis/performance_metrics/*"
+            require = ["slice"]
+            severity = "warning"
+        "#).unwrap();
+        assert_eq!(config.severity.get("owner-contact"), Some(&RuleLevel::Off));
+        assert_eq!(config.rules[0].severity, RuleLevel::Warning);
+        assert_eq!(config.rules[0].message, None);
+    }
+}
@@ -0,0 +1,152 @@
+//! Embedding of referenced files into data files.
+//!
+//! A value `{"$file": "plots/roc.png"}` is replaced by the content of the file as data URI
+//! (`data:image/png;base64,...`), so images like evaluation plots can be kept as files next to the data files.
+//! Paths are resolved relative to the data file containing the reference.
+
+use std::{fs, path::Path};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+use anyhow::{Context, Result};
+
+/// Key of the directive to embed a file.
+pub const FILE_KEY: &str = "$file";
+
+/// Replace all `{"$file": "<path>"}` values by data URIs of the referenced files.
+pub(crate) fn embed_files(value: &mut Value, base: &Path) -> Result<()> {
+    match value {
+        Value::Object(map) => {
+            if let (1, Some(Value::String(file))) = (map.len(), map.get(FILE_KEY)) {
+                let path = base.join(file);
+                let content = fs::read(&path)
+                    .with_context(|| format!("Could not read embedded file '{}'", path.display()))?;
+                *value = Value::String(format!("data:{};base64,{}", mime_type(&path, &content), STANDARD.encode(&content)));
+                return Ok(());
+            }
+            for child in map.values_mut() {
+                embed_files(child, base)?;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                embed_files(item, base)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Detect the MIME type of a file by its extension, falling back to its content.
+///
+/// Knows PNG, JPEG, GIF, WebP, BMP, SVG, PDF, CSV and text files by extension. Files with another or no
+/// extension are detected by the signature of PNG, JPEG, GIF, WebP, BMP, SVG and PDF files; unknown files are
+/// `application/octet-stream`.
+///
+/// ## Example
+///
+/// ```rust
+/// use std::path::Path;
+/// use modelcards::merge::mime_type;
+///
+/// assert_eq!(mime_type(Path::new("roc"), b"\x89PNG\r\n\x1a\n"), "image/png");
+/// assert_eq!(mime_type(Path::new("plots/roc.svg"), b""), "image/svg+xml");
+/// ```
+///
+pub fn mime_type(path: &Path, content: &[u8]) -> &'static str {
+    let extension = path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("bmp") => "image/bmp",
+        Some("svg") => "image/svg+xml",
+        Some("pdf") => "application/pdf",
+        Some("csv") => "text/csv",
+        Some("txt") => "text/plain",
+        _ => sniff(content).unwrap_or("application/octet-stream"),
+    }
+}
+
+/// Detect the MIME type of a file by the signature at the start of its content.
+fn sniff(content: &[u8]) -> Option<&'static str> {
+    let head = &content[..content.len().min(1024)];
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some("image/png");
+    }
+    if head.starts_with(b"\xFF\xD8\xFF") {
+        return Some("image/jpeg");
+    }
+    if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        return Some("image/gif");
+    }
+    if head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    if is_bmp(content) {
+        return Some("image/bmp");
+    }
+    if head.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+    if String::from_utf8_lossy(head).contains("<svg") {
+        return Some("image/svg+xml");
+    }
+    None
+}
+
+/// Check the header of a BMP file, as the `BM` magic alone also starts text files.
+/// The header stores the file size and is followed by a DIB header of a known size.
+fn is_bmp(content: &[u8]) -> bool {
+    let read_u32 = |offset: usize| u32::from_le_bytes([content[offset], content[offset + 1], content[offset + 2], content[offset + 3]]);
+    content.len() >= 18
+        && content.starts_with(b"BM")
+        && read_u32(2) as usize == content.len()
+        && matches!(read_u32(14), 12 | 40 | 52 | 56 | 64 | 108 | 124)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use serde_json::json;
+
+    #[test]
+    fn test_mime_type_by_content() {
+        assert_eq!(mime_type(Path::new("a.bin"), b"\xFF\xD8\xFF\xE0"), "image/jpeg");
+        assert_eq!(mime_type(Path::new("a.bin"), b"GIF89a..."), "image/gif");
+        assert_eq!(mime_type(Path::new("a.bin"), b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(mime_type(Path::new("a.bin"), b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), "image/svg+xml");
+        assert_eq!(mime_type(Path::new("a.bin"), b"data"), "application/octet-stream");
+        let mut bmp = b"BM\x1e\0\0\0\0\0\0\0\x1a\0\0\0\x0c\0\0\0".to_vec();
+        bmp.resize(30, 0);
+        assert_eq!(mime_type(Path::new("a.bin"), &bmp), "image/bmp");
+    }
+
+    #[test]
+    fn test_mime_type_by_extension() {
+        assert_eq!(mime_type(Path::new("a.jpg"), b"\x89PNG\r\n\x1a\n"), "image/jpeg");
+        assert_eq!(mime_type(Path::new("scores.csv"), b"BMI,score\n21.5,0.9\n"), "text/csv");
+        assert_eq!(mime_type(Path::new("notes.TXT"), b"BM25 baseline"), "text/plain");
+        // a text file starting with the BMP magic has no BMP header
+        assert_eq!(mime_type(Path::new("notes"), b"BMI,score\n21.5,0.9\n"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_embed_files() {
+        let dir = temp_dir().join("test_embed_files");
+        fs::create_dir_all(dir.join("plots")).unwrap();
+        fs::write(dir.join("plots/roc.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+        let mut value = json!({"graphics": {"collection": [
+            {"name": "ROC", "image": {"$file": "plots/roc.png"}},
+            {"name": "Other", "image": {"$file": "plots/roc.png", "note": "not a reference"}}
+        ]}});
+        embed_files(&mut value, &dir).unwrap();
+        assert_eq!(value["graphics"]["collection"][0]["image"], json!("data:image/png;base64,iVBORw0KGgo="));
+        assert_eq!(value["graphics"]["collection"][1]["image"]["$file"], json!("plots/roc.png"));
+        assert!(embed_files(&mut json!({"image": {"$file": "missing.png"}}), &dir).is_err());
+    }
+}
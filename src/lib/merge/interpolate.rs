@@ -0,0 +1,262 @@
+//! Interpolation of references in string values of a merged modelcard.
+//!
+//! String values can reference other values with `${...}` expressions, which are resolved after all layers are merged:
+//!
+//! - `${/model_details/name}` - the value at the JSON pointer in the merged modelcard.
+//! - `${env:BUILD_ID}` - the environment variable `BUILD_ID`.
+//! - `${config:project_dir}` - the configuration variable `project_dir` (see [`super::MergeOptions::variables`]).
+//!
+//! A string consisting of a single pointer reference is replaced by the referenced value, keeping its type.
+//! References embedded in text are replaced by the text of the referenced string, number, boolean or `null`.
+//! Referenced values are interpolated first, so references can be chained. `$${` is written as literal `${`.
+//! Other `${...}` text, e.g. `${HOME}` or an unclosed `${`, is not a reference and kept as written.
+//! References to environment or config variables that are not set are kept as written with a warning,
+//! unless interpolation is strict.
+
+use std::collections::{BTreeMap, HashMap};
+use serde_json::Value;
+use anyhow::{bail, Context, Result};
+
+use crate::utils::console;
+use super::escape_pointer_token;
+
+/// Start of an interpolation expression.
+const OPEN: &str = "${";
+/// Escaped start of an interpolation expression, written as literal `${`.
+const ESCAPED_OPEN: &str = "$${";
+
+/// Resolve all `${...}` expressions in the string values of `value`.
+/// With `strict` unset variables are errors instead of being kept as written.
+pub(crate) fn interpolate(value: &mut Value, variables: &BTreeMap<String, String>, strict: bool) -> Result<()> {
+    if !contains_expression(value) {
+        return Ok(());
+    }
+    let root = value.clone();
+    let mut resolver = Resolver { root: &root, variables, strict, stack: vec![], resolved: HashMap::new() };
+    *value = resolver.resolve("")?;
+    Ok(())
+}
+
+fn contains_expression(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s.contains(OPEN),
+        Value::Array(items) => items.iter().any(contains_expression),
+        Value::Object(map) => map.values().any(contains_expression),
+        _ => false,
+    }
+}
+
+/// A part of a string value, either literal text or an expression.
+enum Part<'a> {
+    Text(String),
+    Expression(&'a str),
+}
+
+/// Whether an expression is a pointer, `env:` or `config:` reference.
+fn is_reference(expression: &str) -> bool {
+    expression.starts_with('/') || expression.starts_with("env:") || expression.starts_with("config:")
+}
+
+fn parse(text: &str) -> Vec<Part<'_>> {
+    let mut parts = vec![];
+    let mut literal = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        literal.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with(ESCAPED_OPEN) {
+            literal.push_str(OPEN);
+            rest = &rest[ESCAPED_OPEN.len()..];
+        } else if rest.starts_with(OPEN) {
+            let end = match rest.find('}') {
+                Some(end) if is_reference(&rest[OPEN.len()..end]) => end,
+                _ => {
+                    literal.push_str(OPEN);
+                    rest = &rest[OPEN.len()..];
+                    continue;
+                }
+            };
+            if !literal.is_empty() {
+                parts.push(Part::Text(std::mem::take(&mut literal)));
+            }
+            parts.push(Part::Expression(&rest[OPEN.len()..end]));
+            rest = &rest[end + 1..];
+        } else {
+            literal.push('$');
+            rest = &rest[1..];
+        }
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        parts.push(Part::Text(literal));
+    }
+    parts
+}
+
+struct Resolver<'a> {
+    root: &'a Value,
+    variables: &'a BTreeMap<String, String>,
+    /// Fail on unset variables instead of keeping them as written.
+    strict: bool,
+    /// Pointers currently being resolved, used to detect cycles.
+    stack: Vec<String>,
+    /// Pointers already resolved.
+    resolved: HashMap<String, Value>,
+}
+
+impl Resolver<'_> {
+    /// Resolve the value at `pointer` of the merged modelcard.
+    fn resolve(&mut self, pointer: &str) -> Result<Value> {
+        if let Some(value) = self.resolved.get(pointer) {
+            return Ok(value.clone());
+        }
+        if self.stack.iter().any(|p| p == pointer) {
+            let chain: Vec<&str> = self.stack.iter()
+                .skip_while(|p| *p != pointer)
+                .map(|p| p.as_str())
+                .chain(std::iter::once(pointer))
+                .collect();
+            bail!("Cycle in interpolation: {}", chain.join(" -> "));
+        }
+        let value = match self.root.pointer(pointer) {
+            Some(value) => value,
+            None => bail!("Reference to missing value '{}'", pointer),
+        };
+        self.stack.push(pointer.to_string());
+        let result = match value {
+            Value::String(text) => self.expand(text)
+                .with_context(|| format!("Could not interpolate value at '{}'", pointer))?,
+            Value::Array(items) => Value::Array((0..items.len())
+                .map(|index| self.resolve(&format!("{}/{}", pointer, index)))
+                .collect::<Result<_>>()?),
+            Value::Object(map) => Value::Object(map.keys()
+                .map(|key| Ok((key.clone(), self.resolve(&format!("{}/{}", pointer, escape_pointer_token(key)))?)))
+                .collect::<Result<_>>()?),
+            other => other.clone(),
+        };
+        self.stack.pop();
+        self.resolved.insert(pointer.to_string(), result.clone());
+        Ok(result)
+    }
+
+    /// Expand all expressions of a string value.
+    fn expand(&mut self, text: &str) -> Result<Value> {
+        if !text.contains('$') {
+            return Ok(Value::String(text.to_string()));
+        }
+        let parts = parse(text);
+        if let [Part::Expression(expression)] = parts.as_slice() {
+            return self.evaluate(expression);
+        }
+        let mut result = String::new();
+        for part in parts {
+            match part {
+                Part::Text(literal) => result.push_str(&literal),
+                Part::Expression(expression) => match self.evaluate(expression)? {
+                    Value::String(s) => result.push_str(&s),
+                    value @ (Value::Number(_) | Value::Bool(_) | Value::Null) => result.push_str(&value.to_string()),
+                    _ => bail!("Can not insert array or object of '${{{}}}' into text", expression),
+                },
+            }
+        }
+        Ok(Value::String(result))
+    }
+
+    fn evaluate(&mut self, expression: &str) -> Result<Value> {
+        if expression.starts_with('/') {
+            self.resolve(expression)
+        } else if let Some(name) = expression.strip_prefix("env:") {
+            match std::env::var(name) {
+                Ok(value) => Ok(Value::String(value)),
+                Err(_) => self.unset(expression, format!("Environment variable '{}' is not set", name)),
+            }
+        } else if let Some(name) = expression.strip_prefix("config:") {
+            match self.variables.get(name) {
+                Some(value) => Ok(Value::String(value.clone())),
+                None => self.unset(expression, format!("Unknown config variable '{}'", name)),
+            }
+        } else {
+            bail!("Unknown expression '${{{}}}' (expected a JSON pointer, env:<NAME> or config:<NAME>)", expression)
+        }
+    }
+
+    /// Keep the expression of an unset variable as written, or fail in strict mode.
+    fn unset(&self, expression: &str, message: String) -> Result<Value> {
+        if self.strict {
+            bail!(message);
+        }
+        console::warn(&format!("{}, '${{{}}}' is kept as written", message, expression));
+        Ok(Value::String(format!("${{{}}}", expression)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn interpolated(mut value: Value) -> Result<Value> {
+        let variables = BTreeMap::from([("project_dir".to_string(), "/projects/card".to_string())]);
+        interpolate(&mut value, &variables, true)?;
+        Ok(value)
+    }
+
+    #[test]
+    fn test_pointer_references() {
+        let value = json!({
+            "model_details": {"name": "Model", "version": {"name": "1.2"}, "owners": [{"name": "Jane"}]},
+            "title": "${/model_details/name} v${/model_details/version/name}",
+            "version": "${/model_details/version}",
+            "contact": "${/model_details/owners/0/name}"
+        });
+        let result = interpolated(value).unwrap();
+        assert_eq!(result["title"], json!("Model v1.2"));
+        assert_eq!(result["version"], json!({"name": "1.2"}));
+        assert_eq!(result["contact"], json!("Jane"));
+    }
+
+    #[test]
+    fn test_chained_references() {
+        let value = json!({"a": "${/b}!", "b": "${/c} world", "c": "hello", "n": "count ${/d}", "d": 3});
+        let result = interpolated(value).unwrap();
+        assert_eq!(result, json!({"a": "hello world!", "b": "hello world", "c": "hello", "n": "count 3", "d": 3}));
+    }
+
+    #[test]
+    fn test_env_and_config() {
+        std::env::set_var("MODELCARDS_TEST_BUILD_ID", "42");
+        let value = json!({"build": "build-${env:MODELCARDS_TEST_BUILD_ID}", "path": "${config:project_dir}/plots"});
+        let result = interpolated(value).unwrap();
+        assert_eq!(result, json!({"build": "build-42", "path": "/projects/card/plots"}));
+        assert!(interpolated(json!({"a": "${env:MODELCARDS_TEST_UNSET}"})).is_err());
+        assert!(interpolated(json!({"a": "${config:unknown}"})).is_err());
+        // unset variables are kept as written unless strict
+        let mut value = json!({"a": "built by ${env:MODELCARDS_TEST_UNSET}", "b": "${config:unknown}", "c": "${/a}"});
+        interpolate(&mut value, &BTreeMap::new(), false).unwrap();
+        assert_eq!(value, json!({"a": "built by ${env:MODELCARDS_TEST_UNSET}", "b": "${config:unknown}", "c": "built by ${env:MODELCARDS_TEST_UNSET}"}));
+    }
+
+    #[test]
+    fn test_escape() {
+        let value = json!({"a": "$${/b} costs $5", "b": "x"});
+        let result = interpolated(value).unwrap();
+        assert_eq!(result["a"], json!("${/b} costs $5"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = format!("{:?}", interpolated(json!({"a": "${/b}", "b": "${/a}"})).unwrap_err());
+        assert!(error.contains("Cycle in interpolation: /a -> /b -> /a"), "{}", error);
+        assert!(interpolated(json!({"a": "${/a}"})).is_err());
+        assert!(interpolated(json!({"a": {"b": "${/a}"}})).is_err());
+        assert!(interpolated(json!({"a": "${/missing}"})).is_err());
+        assert!(interpolated(json!({"a": "list: ${/b}", "b": [1]})).is_err());
+    }
+
+    #[test]
+    fn test_other_text_is_kept() {
+        let value = json!({"a": "${/b", "b": "run with ${HOME} set", "c": "${name} and ${/d}", "d": "x"});
+        let result = interpolated(value.clone()).unwrap();
+        assert_eq!(result, json!({"a": "${/b", "b": "run with ${HOME} set", "c": "${name} and x", "d": "x"}));
+    }
+}
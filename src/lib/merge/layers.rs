@@ -0,0 +1,191 @@
+//! Loading of source files into the ordered list of layers to merge.
+//!
+//! Sources can be files, directories or glob patterns. Directories and patterns are expanded into the data
+//! files they contain (see [`expand_source`]). The source `-` is read from stdin. Every source file becomes one layer.
+//!
+//! Data files can declare their own parents with the `$extends` directive, which are loaded (recursively) before
+//! the declaring file. Each file is loaded only once, so a parent shared by several files or also given as source
+//! is not applied twice.
+
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf}
+};
+use serde_json::Value;
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    patch::is_patch_file,
+    utils::{parse_data, read_source, DataFormat}
+};
+use super::{embed_files, MergeOptions};
+
+/// Key of the directive to declare the parents of a data file.
+pub const EXTENDS_KEY: &str = "$extends";
+
+/// A single source file to be merged.
+pub(crate) struct Layer {
+    /// The path of the source file.
+    pub source: String,
+    /// The content of the source file.
+    pub value: Value,
+    /// Whether the layer is a JSON Patch document instead of a modelcard fragment.
+    pub patch: bool,
+}
+
+/// Load all sources and their parents into an ordered list of layers.
+pub(crate) fn load_layers(sources: Vec<String>, options: &MergeOptions) -> Result<Vec<Layer>> {
+    let mut loader = Loader { options, stack: vec![], seen: HashSet::new(), layers: vec![] };
+    for source in sources {
+        for file in expand_source(&source, options.numeric_prefixes)? {
+            loader.load(&file)?;
+        }
+    }
+    Ok(loader.layers)
+}
+
+/// Expand a source into the data files to merge.
+/// 
+/// - A directory is expanded into all data files (`.json`, `.yaml`, `.yml`, `.toml`) directly inside it. Hidden files, Markdown files and sub directories are ignored.
+/// - A glob pattern (containing `*`, `?` or `[`) is expanded into all matching files.
+/// - Any other source is returned unchanged.
+/// 
+/// Files are sorted by their path, comparing the file names byte by byte. If `numeric_prefixes` is set, file names
+/// starting with a number (e.g. `00-defaults.json`, `10-model.json`) are sorted by that number first and come before
+/// all files without a numeric prefix, so `2-usecase.json` is merged before `10-model.json`.
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use modelcards::merge::expand_source;
+/// 
+/// let files = expand_source("layers/*.json", true).unwrap();
+/// ```
+pub fn expand_source(source: &str, numeric_prefixes: bool) -> Result<Vec<String>> {
+    let path = Path::new(source);
+    let mut files: Vec<PathBuf> = if path.is_dir() {
+        let mut files = vec![];
+        for entry in fs::read_dir(path).with_context(|| format!("Could not read source directory '{}'", source))? {
+            let file = entry?.path();
+            let hidden = file.file_name().and_then(|name| name.to_str()).is_none_or(|name| name.starts_with('.'));
+            if file.is_file() && !hidden && DataFormat::from_path(&file).is_some_and(|format| format != DataFormat::Markdown) {
+                files.push(file);
+            }
+        }
+        files
+    } else if !path.exists() && source.contains(['*', '?', '[']) {
+        let mut files = vec![];
+        for entry in glob::glob(source).with_context(|| format!("Invalid glob pattern '{}'", source))? {
+            let file = entry?;
+            if file.is_file() {
+                files.push(file);
+            }
+        }
+        if files.is_empty() {
+            bail!("No files match the pattern '{}'", source);
+        }
+        files
+    } else {
+        return Ok(vec![source.to_string()]);
+    };
+    files.sort_by(|a, b| compare_paths(a, b, numeric_prefixes));
+    Ok(files.into_iter().map(|file| file.to_string_lossy().to_string()).collect())
+}
+
+fn compare_paths(a: &Path, b: &Path, numeric_prefixes: bool) -> Ordering {
+    a.parent().cmp(&b.parent()).then_with(|| {
+        let a = a.file_name().unwrap_or_default().to_string_lossy();
+        let b = b.file_name().unwrap_or_default().to_string_lossy();
+        if !numeric_prefixes {
+            return a.cmp(&b);
+        }
+        match (numeric_prefix(&a), numeric_prefix(&b)) {
+            (Some(x), Some(y)) => x.cmp(&y).then_with(|| a.cmp(&b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => a.cmp(&b),
+        }
+    })
+}
+
+fn numeric_prefix(name: &str) -> Option<u64> {
+    let digits: String = name.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+struct Loader<'a> {
+    options: &'a MergeOptions,
+    /// Files currently being loaded, used to detect cycles in `$extends`.
+    stack: Vec<PathBuf>,
+    /// Files already loaded.
+    seen: HashSet<PathBuf>,
+    layers: Vec<Layer>,
+}
+
+impl Loader<'_> {
+    fn load(&mut self, source: &str) -> Result<()> {
+        let path = Path::new(source);
+        let content = match read_source(source) {
+            Ok(content) => content,
+            Err(e) => bail!("Could not read source '{}': {:#}", source, e),
+        };
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if self.stack.contains(&canonical) {
+            let chain: Vec<String> = self.stack.iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            bail!("Cycle in {} directives: {}", EXTENDS_KEY, chain.join(" -> "));
+        }
+        if !self.seen.insert(canonical.clone()) {
+            return Ok(());
+        }
+
+        let format = self.options.format
+            .or_else(|| DataFormat::from_path(path))
+            .unwrap_or_default();
+        let mut value = parse_data(&content, format)
+            .with_context(|| format!("Invalid {} in source '{}'", format, source))?;
+        let patch = is_patch_file(source);
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        embed_files(&mut value, base)
+            .with_context(|| format!("Could not embed files into source '{}'", source))?;
+
+        if !patch {
+            let parents = take_extends(&mut value)
+                .with_context(|| format!("Invalid {} directive in source '{}'", EXTENDS_KEY, source))?;
+            if !parents.is_empty() {
+                self.stack.push(canonical);
+                for parent in parents {
+                    let parent = base.join(parent);
+                    self.load(&parent.to_string_lossy())
+                        .with_context(|| format!("Could not load parent '{}' of source '{}'", parent.display(), source))?;
+                }
+                self.stack.pop();
+            }
+        }
+
+        self.layers.push(Layer { source: source.to_string(), value, patch });
+        Ok(())
+    }
+}
+
+/// Remove the `$extends` directive from a data file and return the declared parents.
+fn take_extends(value: &mut Value) -> Result<Vec<String>> {
+    let directive = match value.as_object_mut().and_then(|map| map.remove(EXTENDS_KEY)) {
+        Some(directive) => directive,
+        None => return Ok(vec![]),
+    };
+    match directive {
+        Value::String(parent) => Ok(vec![parent]),
+        Value::Array(parents) => parents.into_iter()
+            .map(|parent| match parent {
+                Value::String(parent) => Ok(parent),
+                other => bail!("Parent must be a path string, found {}", other),
+            })
+            .collect(),
+        other => bail!("Expected a path or a list of paths, found {}", other),
+    }
+}
@@ -0,0 +1,1231 @@
+//! Merge JSON files or strings into a single JSON object.
+//! 
+//! The module provides functions to merge multiple JSON files or strings into a single JSON object.
+//! 
+//! ## Functions
+//! 
+//! The module provides the following functions:
+//! 
+//! - `from_paths(sources: Vec<String>) -> Result<Value>` - Merge multiple JSON files into a single JSON object.
+//! - `from_paths_with(sources: Vec<String>, options: &MergeOptions) -> Result<Value>` - Merge multiple JSON files using the given merge options.
+//! - `from_paths_with_provenance(sources: Vec<String>, options: &MergeOptions) -> Result<(Value, Provenance)>` - Merge multiple JSON files and track which file set each value.
+//! - `load_modelcard(path: &Path) -> Result<Value>` - Load a single modelcard data file including its `$extends` parents.
+//! - `from_strings(strings: Vec<String>) -> Result<Value>` - Merge multiple JSON strings into a single JSON object.
+//! - `from_strings_with(strings: Vec<String>, options: &MergeOptions) -> Result<Value>` - Merge multiple JSON strings using the given merge options.
+//! - `merge_with(a: &mut Value, b: Value, options: &MergeOptions)` - Merge two JSON values recursively using the given merge options.
+//! - `three_way(base: &Value, ours: &Value, theirs: &Value, markers: bool) -> (Value, Vec<Conflict>)` - Merge the changes of two modelcards relative to their common base.
+//! 
+//! ## Array strategies
+//! 
+//! By default arrays of a later layer replace arrays of an earlier layer. The [`ArrayStrategy`] in
+//! [`MergeOptions`] allows to append, prepend or union arrays instead, or to merge entries of arrays of
+//! objects by a key field (e.g. `name` or `type`). Strategies can be set for all arrays or for single
+//! locations addressed by JSON pointer (e.g. `/considerations/limitations`).
+//! 
+//! ## Data formats
+//! 
+//! Besides JSON, sources can be written in YAML or TOML. The format of files is detected by their extension
+//! (`.json`, `.yaml`, `.yml`, `.toml`) or set explicitly with [`MergeOptions::format`]. All formats are
+//! normalised into a [`serde_json::Value`] before merging.
+//! 
+//! ## Directories and glob patterns
+//! 
+//! Sources can also be directories or glob patterns (e.g. `layers/*.json`), which are expanded into the data files they
+//! contain in a deterministic order: sorted by path, and optionally by numeric file name prefixes like `00-defaults.json`
+//! (see [`expand_source`]).
+//! 
+//! ## Inheritance
+//! 
+//! A data file can declare its own parents with the `$extends` directive, e.g. `"$extends": ["../defaults.json", "usecase.json"]`.
+//! Parents are resolved relative to the declaring file and merged before it (in the given order, recursively),
+//! so callers don't need to know the right layer order. Every file is merged only once, cycles are reported as
+//! error and the directive is removed from the merged result.
+//! 
+//! ## Patch layers
+//! 
+//! Source files ending with `.patch.json` contain JSON Patch ([RFC 6902](https://www.rfc-editor.org/rfc/rfc6902))
+//! operations instead of a modelcard fragment. They are applied to the result of all previous layers, which allows
+//! inserting into the middle of arrays, moving values or asserting preconditions with `test`.
+//! 
+//! ## Provenance
+//! 
+//! [`from_paths_with_provenance`] additionally returns a [`Provenance`] map, which tells for every leaf value
+//! of the merged result which source file set it and which values of earlier files it shadowed.
+//! 
+//! ## Type conflicts
+//! 
+//! When a later file changes the type of a value (e.g. replaces an object with a string or an array with a number),
+//! the conflict is logged as warning with the JSON pointer and both source files. With [`MergeOptions::strict`] set,
+//! merging fails instead. `null` values are compatible with any type and changes by patch layers are not reported.
+//! 
+//! ## Embedded files
+//! 
+//! A value `{"$file": "plots/roc.png"}` in a data file is replaced by the referenced file as base64 encoded
+//! data URI with the MIME type of its extension or content (e.g. `data:image/png;base64,...`), so images can be kept as PNG or SVG files.
+//! Paths are resolved relative to the data file containing the reference.
+//! 
+//! ## Interpolation
+//! 
+//! With [`MergeOptions::interpolate`] set, string values can reference other values after merging, so one
+//! authoritative value flows everywhere: `${/model_details/name}` inserts the value at the JSON pointer of the
+//! merged modelcard, `${env:BUILD_ID}` an environment variable and `${config:project_dir}` a variable of
+//! [`MergeOptions::variables`]. Cyclic references are rejected and `$${` is kept as literal `${`, as is any
+//! other `${...}` text. Variables that are not set are kept as written with a warning, or rejected with
+//! [`MergeOptions::strict`].
+//! 
+//! ## Three-way merge
+//! 
+//! [`three_way`] merges two modelcards derived from a common base, e.g. a card with regenerated metrics and a card
+//! with edited prose. Changes made on only one side are taken over; values changed differently on both sides are
+//! reported as [`Conflict`] by JSON pointer and can be written as `{"$conflict": {...}}` markers.
+//! 
+//! ## Merge modes
+//! 
+//! The default [`MergeMode::Deep`] keeps `null` values of later layers as literal values.
+//! With [`MergeMode::Patch`] every layer is applied as a JSON Merge Patch ([RFC 7396](https://www.rfc-editor.org/rfc/rfc7396)),
+//! so a `null` value removes the field inherited from earlier layers.
+//! 
+//! ## Errors
+//! 
+//! The functions will return an error if any of the files are not found or don't include valid JSON (or YAML/TOML) strings, or if a patch layer can not be applied.
+//! The anyhow crate is used for error handling.
+//! 
+//! ## Example
+//! 
+//! ```rust,no_run
+//! use serde_json::json;
+//! use modelcards::merge::from_paths;
+//! 
+//! let sources = vec![
+//!     "tests/data/a.json".to_string(), // contains {"a": 1}
+//!     "tests/data/b.json".to_string()   // contains {"b": 2}
+//! ];
+//! let result = from_paths(sources).unwrap();
+//! let expected = json!({
+//!     "a": 1,
+//!     "b": 2
+//! });
+//! assert_eq!(result, expected);
+//! ```
+//! 
+
+use std::{collections::BTreeMap, fmt, path::Path, str::FromStr};
+use serde_json::Value;
+use anyhow::{bail, Context, Result};
+use crate::{
+    patch::apply_patch,
+    utils::{console, parse_data, DataFormat}
+};
+
+mod layers;
+pub use layers::{expand_source, EXTENDS_KEY};
+use layers::{load_layers, Layer};
+mod embed;
+pub use embed::{mime_type, FILE_KEY};
+use embed::embed_files;
+mod interpolate;
+use interpolate::interpolate;
+mod provenance;
+mod three_way;
+pub use three_way::{three_way, Conflict, CONFLICT_KEY};
+pub use provenance::{leaves, type_name, Origin, Provenance, Shadowed, TypeConflict};
+use provenance::Recorder;
+
+
+/* fn merge(a: &Value, b: &Value) -> Value {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut res = a.clone();
+            for (k, v) in b {
+                res.insert(k.clone(), v.clone());
+            }
+            Value::Object(res)
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            let mut res = a.clone();
+            res.extend(b.clone());
+            Value::Array(res)
+        }
+        (_, b) => b.clone(),
+    }
+}
+*/
+
+
+/// Merge multiple JSON files into a single JSON object.
+/// 
+/// The function takes a vector of file paths and merges the JSON objects in the files into a single JSON object.
+/// 
+/// ## Arguments
+/// 
+/// - `sources` - A vector of file paths to JSON files to be merged.
+/// 
+/// ## Returns
+/// 
+/// The function returns a `Result` with the merged JSON object or an error if the files are not found or don't include valid JSON strings.
+/// 
+/// ## Errors
+/// 
+/// The function will return an error if any of the files are not found or don't include valid JSON strings.
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use serde_json::json;
+/// use modelcards::merge::from_paths;
+/// 
+/// let sources = vec![
+///     "tests/data/a.json".to_string(), // contains {"a": 1}
+///     "tests/data/b.json".to_string()   // contains {"b": 2}
+/// ];
+/// let result = from_paths(sources).unwrap();
+/// let expected = json!({
+///     "a": 1,
+///     "b": 2
+/// });
+/// assert_eq!(result, expected);
+/// ```
+/// 
+ pub fn from_paths(sources: Vec<String>) -> Result<Value> {
+    from_paths_with(sources, &MergeOptions::default())
+}
+
+/// Merge multiple JSON files into a single JSON object using the given merge options.
+/// 
+/// Works like [`from_paths`], but uses the given [`MergeOptions`] to decide how layers are merged.
+/// 
+/// Files ending with `.patch.json` are not merged, but applied as JSON Patch (RFC 6902) to the result
+/// of all previous files (see [`crate::patch`]).
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use modelcards::merge::{from_paths_with, ArrayStrategy, MergeOptions};
+/// 
+/// let options = MergeOptions { arrays: ArrayStrategy::Append, ..Default::default() };
+/// let sources = vec![
+///     "defaults.json".to_string(),
+///     "model.json".to_string()
+/// ];
+/// let result = from_paths_with(sources, &options).unwrap();
+/// ```
+/// 
+pub fn from_paths_with(sources: Vec<String>, options: &MergeOptions) -> Result<Value> {
+    merge_paths(sources, options, None)
+}
+
+/// Merge multiple JSON files and track which file set each value.
+/// 
+/// Works like [`from_paths_with`], but additionally returns the [`Provenance`] of the merged result:
+/// a map from the JSON pointer of every leaf value to the source file which set it, together with
+/// the values of earlier files it overrode.
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use modelcards::merge::{from_paths_with_provenance, MergeOptions};
+/// 
+/// let sources = vec![
+///     "defaults.json".to_string(),
+///     "model.json".to_string()
+/// ];
+/// let (merged, provenance) = from_paths_with_provenance(sources, &MergeOptions::default()).unwrap();
+/// if let Some(origin) = provenance.get("/model_details/name") {
+///     println!("name set by {}", origin.source);
+/// }
+/// ```
+/// 
+pub fn from_paths_with_provenance(sources: Vec<String>, options: &MergeOptions) -> Result<(Value, Provenance)> {
+    let mut provenance = Provenance::new();
+    let merged = merge_paths(sources, options, Some(&mut provenance))?;
+    Ok((merged, provenance))
+}
+
+/// Load a single modelcard data file.
+/// 
+/// In contrast to [`crate::utils::load_json_file`] the parents declared with `$extends` are resolved and merged.
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use std::path::Path;
+/// use modelcards::merge::load_modelcard;
+/// 
+/// let modelcard = load_modelcard(Path::new("model.json")).unwrap();
+/// ```
+/// 
+pub fn load_modelcard(path: &Path) -> Result<Value> {
+    from_paths(vec![path.to_string_lossy().to_string()])
+}
+
+fn merge_paths(sources: Vec<String>, options: &MergeOptions, provenance: Option<&mut Provenance>) -> Result<Value> {
+    let layers = load_layers(sources, options)
+        .context("Failed to merge modelcard files")?;
+    // provenance is always tracked to report type conflicts with the source files involved
+    let mut local = Provenance::new();
+    let provenance = provenance.unwrap_or(&mut local);
+    let mut conflicts = vec![];
+    let mut result = Value::Object(serde_json::Map::new());
+    for Layer { source, value: json, patch } in layers {
+        let mut recorder = Recorder::new(provenance, &mut conflicts, &source);
+        if patch {
+            let before = result.clone();
+            apply_patch(&mut result, &json)
+                .with_context(|| format!("Could not apply patch '{}'", source))
+                .context("Failed to merge modelcard files")?;
+            recorder.diff(&before, &result);
+        } else {
+            merge_at(&mut result, json, options, "", Some(&mut recorder));
+        }
+    }
+    if !conflicts.is_empty() {
+        let conflicts: Vec<String> = conflicts.iter().map(|conflict| conflict.to_string()).collect();
+        if options.strict {
+            bail!("Type conflicts between layers:\n - {}", conflicts.join("\n - "));
+        }
+        for conflict in conflicts {
+            console::warn(&format!("Type conflict at {}", conflict));
+        }
+    }
+    if options.interpolate {
+        interpolate(&mut result, &options.variables, options.strict).context("Failed to interpolate modelcard values")?;
+    }
+    Ok(result)
+}
+ 
+/// Merge multiple JSON strings into a single JSON object.
+/// 
+/// The function takes a vector of JSON strings and merges them into a single JSON object.
+/// 
+/// ## Arguments
+/// 
+/// - `strings` - A vector of valid JSON strings to be merged.
+/// 
+/// ## Returns
+/// 
+/// The function returns a `Result` with the merged JSON object or an error if the JSON strings are invalid.
+/// 
+/// ## Errors
+/// 
+/// The function will return an error if any of the JSON strings are invalid.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::merge::from_strings;
+/// 
+/// let strings = vec![
+///     "{\"a\": 1}".to_string(),
+///     "{\"b\": 2}".to_string()
+/// ];
+/// let result = from_strings(strings).unwrap();
+/// let expected = json!({
+///     "a": 1,
+///     "b": 2
+/// });
+/// assert_eq!(result, expected);
+/// ```
+/// 
+pub fn from_strings(strings: Vec<String>) -> Result<Value> {
+    from_strings_with(strings, &MergeOptions::default())
+}
+
+/// Merge multiple JSON strings into a single JSON object using the given merge options.
+/// 
+/// Works like [`from_strings`], but uses the given [`MergeOptions`] to decide how arrays are merged.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::merge::{from_strings_with, ArrayStrategy, MergeOptions};
+/// 
+/// let strings = vec![
+///     r#"{"limitations": ["slow"]}"#.to_string(),
+///     r#"{"limitations": ["large"]}"#.to_string()
+/// ];
+/// let options = MergeOptions { arrays: ArrayStrategy::Append, ..Default::default() };
+/// let result = from_strings_with(strings, &options).unwrap();
+/// assert_eq!(result, json!({"limitations": ["slow", "large"]}));
+/// ```
+/// 
+pub fn from_strings_with(strings: Vec<String>, options: &MergeOptions) -> Result<Value> {
+    let mut result = Value::Object(serde_json::Map::new());
+    for string in strings {
+        match parse_data(&string, options.format.unwrap_or_default()) {
+            Ok(json) => merge_with(&mut result, json, options),
+            Err(e) => bail!("Invalid {}:\n{string}\n\nError: {:?}", options.format.unwrap_or_default(), e),
+        }
+    }
+    if options.interpolate {
+        interpolate(&mut result, &options.variables, options.strict).context("Failed to interpolate modelcard values")?;
+    }
+    Ok(result)
+}
+
+/// Strategy used to merge two arrays found at the same location.
+/// 
+/// The strategy can be parsed from its name: `replace`, `append`, `prepend`, `union` or
+/// `merge-by-key:<key>` (e.g. `merge-by-key:name`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ArrayStrategy {
+    /// The later array replaces the earlier array (default).
+    #[default]
+    Replace,
+    /// Entries of the later array are added after the entries of the earlier array.
+    Append,
+    /// Entries of the later array are added before the entries of the earlier array.
+    Prepend,
+    /// Entries of the later array are appended, unless an equal entry already exists.
+    Union,
+    /// Objects with the same value in the given key field are merged recursively, all other entries are appended.
+    MergeByKey(String),
+}
+
+impl FromStr for ArrayStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "replace" => Ok(ArrayStrategy::Replace),
+            "append" => Ok(ArrayStrategy::Append),
+            "prepend" => Ok(ArrayStrategy::Prepend),
+            "union" => Ok(ArrayStrategy::Union),
+            other => match other.strip_prefix("merge-by-key:") {
+                Some(key) if !key.is_empty() => Ok(ArrayStrategy::MergeByKey(key.to_string())),
+                _ => bail!("Unknown array strategy '{}' (expected replace, append, prepend, union or merge-by-key:<key>)", other),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ArrayStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArrayStrategy::Replace => write!(f, "replace"),
+            ArrayStrategy::Append => write!(f, "append"),
+            ArrayStrategy::Prepend => write!(f, "prepend"),
+            ArrayStrategy::Union => write!(f, "union"),
+            ArrayStrategy::MergeByKey(key) => write!(f, "merge-by-key:{}", key),
+        }
+    }
+}
+
+/// Mode used to apply a layer to the merged result.
+/// 
+/// The mode can be parsed from its name: `deep` or `patch`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Recursive merge, where `null` values are kept as literal values (default).
+    #[default]
+    Deep,
+    /// JSON Merge Patch (RFC 7396), where `null` values remove the field from the result.
+    Patch,
+}
+
+impl FromStr for MergeMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "deep" => Ok(MergeMode::Deep),
+            "patch" | "rfc7396" => Ok(MergeMode::Patch),
+            other => bail!("Unknown merge mode '{}' (expected deep or patch)", other),
+        }
+    }
+}
+
+impl fmt::Display for MergeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeMode::Deep => write!(f, "deep"),
+            MergeMode::Patch => write!(f, "patch"),
+        }
+    }
+}
+
+/// Options to control how JSON values are merged.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use modelcards::merge::{ArrayStrategy, MergeOptions};
+/// 
+/// let mut options = MergeOptions::default();
+/// options.add_array_strategy("append").unwrap();
+/// options.add_array_strategy("/model_details/owners=merge-by-key:name").unwrap();
+/// assert_eq!(options.arrays, ArrayStrategy::Append);
+/// assert_eq!(options.array_strategy("/model_details/owners"), &ArrayStrategy::MergeByKey("name".to_string()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    /// Format of the sources. If not set, the format of files is detected by their extension and strings are read as JSON.
+    pub format: Option<DataFormat>,
+    /// Mode used to apply each layer to the merged result.
+    pub mode: MergeMode,
+    /// Sort files of directory and glob sources by their numeric prefix (e.g. `00-defaults.json`) instead of by name only.
+    pub numeric_prefixes: bool,
+    /// Reject type conflicts between layers (e.g. an object replaced by a string) and unset interpolation variables instead of logging them as warnings.
+    pub strict: bool,
+    /// Strategy for all arrays without a location specific strategy.
+    pub arrays: ArrayStrategy,
+    /// Strategies for arrays at specific locations, keyed by JSON pointer (e.g. `/considerations/limitations`).
+    pub array_paths: BTreeMap<String, ArrayStrategy>,
+    /// Resolve `${...}` references in string values after merging (see [Interpolation](self#interpolation)).
+    pub interpolate: bool,
+    /// Variables available as `${config:<name>}` in string values (e.g. `project_dir`).
+    pub variables: BTreeMap<String, String>,
+}
+
+impl MergeOptions {
+    /// Add an array strategy from a `[POINTER=]STRATEGY` specification.
+    /// 
+    /// Without a pointer the strategy becomes the default for all arrays, otherwise it only applies to the array at the given JSON pointer.
+    pub fn add_array_strategy(&mut self, spec: &str) -> Result<()> {
+        match spec.split_once('=') {
+            Some((pointer, strategy)) => {
+                let pointer = pointer.trim();
+                if !pointer.starts_with('/') {
+                    bail!("Invalid JSON pointer '{}' in array strategy '{}'", pointer, spec);
+                }
+                self.array_paths.insert(pointer.to_string(), strategy.parse()?);
+            }
+            None => self.arrays = spec.parse()?,
+        }
+        Ok(())
+    }
+
+    /// Get the array strategy for the array at the given JSON pointer.
+    pub fn array_strategy(&self, pointer: &str) -> &ArrayStrategy {
+        self.array_paths.get(pointer).unwrap_or(&self.arrays)
+    }
+}
+
+/// Merge two JSON values recursively.
+/// 
+/// The function takes two deserialized JSON objects and merges them on value level recursively.
+/// 
+/// ## Arguments
+/// - `a` - A mutable reference to the first JSON value.
+/// - `b` - The second JSON value which will overwrite existing entries in `a` or add them to `a`.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::merge::merge;
+/// 
+/// let mut a = json!({
+///     "a": 1,
+///     "b": 2,
+///     "c": [1, 2, 3],
+///     "d": {
+///         "e": 3,
+///         "f": 4
+///     }
+/// });
+/// let b = json!({
+///     "b": 3,
+///     "c": [4, 5, 6],
+///     "d": {
+///         "f": 5,
+///         "g": 6
+///     }
+/// });
+/// merge(&mut a, b);
+/// let expected = json!({
+///     "a": 1,
+///     "b": 3,
+///     "c": [4, 5, 6],
+///     "d": {
+///         "e": 3,
+///         "f": 5,
+///         "g": 6
+///     }
+/// });
+/// assert_eq!(a, expected);
+/// ```
+/// 
+pub fn merge(a: &mut Value, b: Value) {
+    merge_with(a, b, &MergeOptions::default());
+}
+
+/// Merge two JSON values recursively using the given merge options.
+/// 
+/// Objects are merged on key level, arrays are merged according to the [`ArrayStrategy`] configured
+/// for their location and all other values of `b` overwrite the values in `a`.
+/// In [`MergeMode::Patch`] a `null` value in `b` removes the key from `a`.
+/// 
+/// ## Arguments
+/// - `a` - A mutable reference to the first JSON value.
+/// - `b` - The second JSON value which will be merged into `a`.
+/// - `options` - The merge options to use.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::merge::{merge_with, MergeMode, MergeOptions};
+/// 
+/// let mut options = MergeOptions::default();
+/// options.add_array_strategy("merge-by-key:name").unwrap();
+/// let mut a = json!({"owners": [{"name": "Jane", "contact": "jane@example.com"}]});
+/// let b = json!({"owners": [{"name": "Jane", "contact": "j.doe@example.com"}, {"name": "John"}]});
+/// merge_with(&mut a, b, &options);
+/// assert_eq!(a, json!({"owners": [{"name": "Jane", "contact": "j.doe@example.com"}, {"name": "John"}]}));
+/// 
+/// let options = MergeOptions { mode: MergeMode::Patch, ..Default::default() };
+/// let mut a = json!({"name": "Model", "license": "MIT"});
+/// merge_with(&mut a, json!({"license": null}), &options);
+/// assert_eq!(a, json!({"name": "Model"}));
+/// ```
+/// 
+pub fn merge_with(a: &mut Value, b: Value, options: &MergeOptions) {
+    merge_at(a, b, options, "", None);
+}
+
+fn merge_at(a: &mut Value, b: Value, options: &MergeOptions, pointer: &str, mut recorder: Option<&mut Recorder>) {
+    match (a, b) {
+        (a @ &mut Value::Object(_), Value::Object(b)) => {
+            if let Some(recorder) = recorder.as_deref_mut() {
+                if !b.is_empty() {
+                    recorder.expand(pointer);
+                }
+            }
+            let a = a.as_object_mut().expect("Failed to get mutable object reference");
+            for (k, v) in b {
+                let child = format!("{}/{}", pointer, escape_pointer_token(&k));
+                if v.is_null() && options.mode == MergeMode::Patch {
+                    a.remove(&k);
+                    if let Some(recorder) = recorder.as_deref_mut() {
+                        recorder.remove(&child);
+                    }
+                    continue;
+                }
+                merge_at(a.entry(k).or_insert(Value::Null), v, options, &child, recorder.as_deref_mut());
+            }
+        }
+        (a, b @ Value::Object(_)) if options.mode == MergeMode::Patch => {
+            // RFC 7396: a patch object is always applied to an object, dropping the previous value
+            if let Some(recorder) = recorder.as_deref_mut() {
+                recorder.check_type(pointer, a, &b);
+                recorder.remove(pointer);
+            }
+            *a = Value::Object(serde_json::Map::new());
+            merge_at(a, b, options, pointer, recorder);
+        }
+        (Value::Array(a), Value::Array(b)) => merge_arrays(a, b, options, pointer, recorder),
+        (a, b) => {
+            if let Some(recorder) = recorder {
+                recorder.assign(pointer, a, &b);
+            }
+            *a = b;
+        }
+    }
+}
+
+fn merge_arrays(a: &mut Vec<Value>, b: Vec<Value>, options: &MergeOptions, pointer: &str, mut recorder: Option<&mut Recorder>) {
+    let strategy = options.array_strategy(pointer);
+    if let Some(recorder) = recorder.as_deref_mut() {
+        if *strategy != ArrayStrategy::Replace && !b.is_empty() {
+            recorder.expand(pointer);
+        }
+    }
+    match strategy {
+        ArrayStrategy::Replace => {
+            if let Some(recorder) = recorder {
+                recorder.assign(pointer, &Value::Array(a.clone()), &Value::Array(b.clone()));
+            }
+            *a = b;
+        }
+        ArrayStrategy::Append => {
+            for (index, item) in b.into_iter().enumerate() {
+                push_item(a, item, index, pointer, recorder.as_deref_mut());
+            }
+        }
+        ArrayStrategy::Prepend => {
+            if let Some(recorder) = recorder.as_deref_mut() {
+                recorder.shift(pointer, b.len());
+                for (index, item) in b.iter().enumerate() {
+                    recorder.assign(&format!("{}/{}", pointer, index), &Value::Null, item);
+                }
+            }
+            let tail = std::mem::replace(a, b);
+            a.extend(tail);
+        }
+        ArrayStrategy::Union => {
+            for (index, item) in b.into_iter().enumerate() {
+                if !a.contains(&item) {
+                    push_item(a, item, index, pointer, recorder.as_deref_mut());
+                }
+            }
+        }
+        ArrayStrategy::MergeByKey(key) => {
+            for (index, item) in b.into_iter().enumerate() {
+                let position = item.get(key)
+                    .and_then(|id| a.iter().position(|existing| existing.get(key) == Some(id)));
+                match position {
+                    Some(position) => {
+                        let child = format!("{}/{}", pointer, position);
+                        if let Some(recorder) = recorder.as_deref_mut() {
+                            recorder.move_item(pointer, index, position);
+                        }
+                        merge_at(&mut a[position], item, options, &child, recorder.as_deref_mut());
+                    }
+                    None => push_item(a, item, index, pointer, recorder.as_deref_mut()),
+                }
+            }
+        }
+    }
+}
+
+/// Append the item at `index` of the array at `pointer` in a layer to the merged array.
+fn push_item(a: &mut Vec<Value>, item: Value, index: usize, pointer: &str, recorder: Option<&mut Recorder>) {
+    if let Some(recorder) = recorder {
+        recorder.move_item(pointer, index, a.len());
+        recorder.assign(&format!("{}/{}", pointer, a.len()), &Value::Null, &item);
+    }
+    a.push(item);
+}
+
+/// Escape a key to be used as reference token in a JSON pointer (RFC 6901).
+pub(crate) fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_merge() {
+        let mut a = json!({
+            "a": 1,
+            "b": 2,
+            "c": [1, 2, 3],
+            "d": {
+                "e": 3,
+                "f": 4
+            }
+        });
+        let b = json!({
+            "b": 3,
+            "c": [4, 5, 6],
+            "d": {
+                "f": 5,
+                "g": 6
+            }
+        });
+        merge(&mut a, b);
+        let expected = json!({
+            "a": 1,
+            "b": 3,
+            "c": [4, 5, 6],
+            "d": {
+                "e": 3,
+                "f": 5,
+                "g": 6
+            }
+        });
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn test_merge_overwrite_primitive() {
+        let mut a = json!({
+            "a": 1,
+            "b": 2
+        });
+        let b = json!({
+            "b": 3,
+            "c": 4
+        });
+        merge(&mut a, b);
+        let expected = json!({
+            "a": 1,
+            "b": 3,
+            "c": 4
+        });
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn test_merge_nested_object() {
+        let mut a = json!({
+            "a": {
+                "b": 1,
+                "c": 2
+            }
+        });
+        let b = json!({
+            "a": {
+                "c": 3,
+                "d": 4
+            }
+        });
+        merge(&mut a, b);
+        let expected = json!({
+            "a": {
+                "b": 1,
+                "c": 3,
+                "d": 4
+            }
+        });
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn test_merge_with_array_replacement() {
+        let mut a = json!({
+            "a": [1, 2, 3]
+        });
+        let b = json!({
+            "a": [4, 5, 6]
+        });
+        merge(&mut a, b);
+        let expected = json!({
+            "a": [4, 5, 6]
+        });
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn test_merge_adds_new_elements() {
+        let mut a = json!({
+            "a": 1
+        });
+        let b = json!({
+            "b": 2
+        });
+        merge(&mut a, b);
+        let expected = json!({
+            "a": 1,
+            "b": 2
+        });
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn test_merge_from_strings() {
+        let strings = vec![
+            "{\"a\": 1}".to_string(),
+            "{\"b\": 2}".to_string()
+        ];
+        let result = from_strings(strings).unwrap();
+        let expected = json!({
+            "a": 1,
+            "b": 2
+        });
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_merge_from_strings_with_error() {
+        let strings = vec![
+            "{\"a\": 1}".to_string(),
+            "invalid json".to_string()
+        ];
+        let result = from_strings(strings);
+        assert!(result.is_err());
+    }
+
+    fn write_sources(name: &str, files: &[(&str, &str)]) -> Vec<String> {
+        let dir = std::env::temp_dir().join(name);
+        if dir.exists() {
+            fs::remove_dir_all(&dir).expect("Could not free test directory");
+        }
+        fs::create_dir(&dir).expect("Could not create test directory");
+        files.iter().map(|(file, content)| {
+            let path = dir.join(file);
+            fs::write(&path, content).expect("Could not write test file");
+            path.to_str().expect("Invalid path").to_string()
+        }).collect()
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_patch_layer() {
+        let sources = write_sources("test_merge_from_paths_with_patch_layer", &[
+            ("defaults.json", r#"{"metrics": [{"type": "accuracy"}, {"type": "recall"}], "name": "default"}"#),
+            ("metrics.patch.json", r#"[
+                {"op": "test", "path": "/metrics/0/type", "value": "accuracy"},
+                {"op": "add", "path": "/metrics/1", "value": {"type": "precision"}}
+            ]"#),
+            ("model.json", r#"{"name": "model"}"#),
+        ]);
+        let result = from_paths(sources).unwrap();
+        let expected = json!({
+            "metrics": [{"type": "accuracy"}, {"type": "precision"}, {"type": "recall"}],
+            "name": "model"
+        });
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_failing_patch_layer() {
+        let sources = write_sources("test_merge_from_paths_with_failing_patch_layer", &[
+            ("defaults.json", r#"{"name": "default"}"#),
+            ("check.patch.json", r#"[{"op": "test", "path": "/name", "value": "other"}]"#),
+        ]);
+        let result = from_paths(sources);
+        assert!(result.is_err());
+        assert!(format!("{:?}", result.unwrap_err()).contains("check.patch.json"));
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_mixed_formats() {
+        let sources = write_sources("test_merge_from_paths_with_mixed_formats", &[
+            ("defaults.yaml", "# shared defaults\nmodel_details:\n  name: default\n  owners:\n    - name: Jane\n"),
+            ("usecase.toml", "[model_details]\nname = \"usecase\"\n[considerations]\nusers = [\"analysts\"]\n"),
+            ("model.json", r#"{"model_details": {"name": "model"}}"#),
+        ]);
+        let result = from_paths(sources).unwrap();
+        let expected = json!({
+            "model_details": {"name": "model", "owners": [{"name": "Jane"}]},
+            "considerations": {"users": ["analysts"]}
+        });
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_directory_and_glob() {
+        let sources = write_sources("test_merge_from_paths_with_directory_and_glob", &[
+            ("10-model.json", r#"{"name": "model"}"#),
+            ("2-usecase.yaml", "name: usecase\nusers: [analysts]\n"),
+            ("00-defaults.json", r#"{"name": "default", "license": "MIT"}"#),
+            ("notes.txt", "not a data file"),
+        ]);
+        let dir = Path::new(&sources[0]).parent().expect("Invalid path").to_str().expect("Invalid path").to_string();
+
+        // lexicographic order: 00-defaults.json, 10-model.json, 2-usecase.yaml
+        let result = from_paths(vec![dir.clone()]).unwrap();
+        assert_eq!(result["name"], json!("usecase"));
+
+        // numeric prefix order: 00-defaults.json, 2-usecase.yaml, 10-model.json
+        let options = MergeOptions { numeric_prefixes: true, ..Default::default() };
+        let result = from_paths_with(vec![dir.clone()], &options).unwrap();
+        assert_eq!(result, json!({"name": "model", "license": "MIT", "users": ["analysts"]}));
+
+        let result = from_paths_with(vec![format!("{}/*.json", dir)], &options).unwrap();
+        assert_eq!(result, json!({"name": "model", "license": "MIT"}));
+
+        assert!(from_paths(vec![format!("{}/*.missing", dir)]).is_err());
+    }
+
+    #[test]
+    fn test_expand_source_sort_order() {
+        let sources = write_sources("test_expand_source_sort_order", &[
+            ("b.json", "{}"),
+            ("a.json", "{}"),
+            ("10-x.json", "{}"),
+            ("9-y.toml", ""),
+            (".hidden.json", "{}"),
+        ]);
+        let dir = Path::new(&sources[0]).parent().expect("Invalid path").to_str().expect("Invalid path").to_string();
+        let names = |files: Vec<String>| -> Vec<String> {
+            files.iter().map(|f| Path::new(f).file_name().unwrap().to_str().unwrap().to_string()).collect()
+        };
+        assert_eq!(names(expand_source(&dir, false).unwrap()), vec!["10-x.json", "9-y.toml", "a.json", "b.json"]);
+        assert_eq!(names(expand_source(&dir, true).unwrap()), vec!["9-y.toml", "10-x.json", "a.json", "b.json"]);
+        assert_eq!(expand_source("plain.json", true).unwrap(), vec!["plain.json"]);
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_extends() {
+        let sources = write_sources("test_merge_from_paths_with_extends", &[
+            ("defaults.json", r#"{"name": "default", "license": "MIT", "tags": ["a"]}"#),
+            ("usecase.yaml", "$extends: defaults.json\nname: usecase\nusers: [analysts]\n"),
+            ("model.json", r#"{"$extends": ["defaults.json", "usecase.yaml"], "name": "model"}"#),
+        ]);
+        // defaults.json is given as source and as parent, but must only be merged once
+        let result = from_paths(vec![sources[0].clone(), sources[2].clone()]).unwrap();
+        let expected = json!({"name": "model", "license": "MIT", "tags": ["a"], "users": ["analysts"]});
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_extends_in_subdirectory() {
+        let dir = std::env::temp_dir().join("test_merge_from_paths_with_extends_in_subdirectory");
+        if dir.exists() {
+            fs::remove_dir_all(&dir).expect("Could not free test directory");
+        }
+        fs::create_dir_all(dir.join("models")).expect("Could not create test directory");
+        fs::write(dir.join("defaults.json"), r#"{"name": "default", "version": "1"}"#).expect("Could not write test file");
+        fs::write(dir.join("models/model.json"), r#"{"$extends": "../defaults.json", "name": "model"}"#).expect("Could not write test file");
+        let source = dir.join("models/model.json").to_str().expect("Invalid path").to_string();
+        let (result, provenance) = from_paths_with_provenance(vec![source], &MergeOptions::default()).unwrap();
+        assert_eq!(result, json!({"name": "model", "version": "1"}));
+        assert!(provenance["/version"].source.ends_with("defaults.json"));
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_extends_cycle() {
+        let sources = write_sources("test_merge_from_paths_with_extends_cycle", &[
+            ("a.json", r#"{"$extends": "b.json", "a": 1}"#),
+            ("b.json", r#"{"$extends": "c.json", "b": 1}"#),
+            ("c.json", r#"{"$extends": "a.json", "c": 1}"#),
+        ]);
+        let error = format!("{:?}", from_paths(vec![sources[0].clone()]).unwrap_err());
+        assert!(error.contains("Cycle in $extends"), "{}", error);
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_invalid_extends() {
+        let sources = write_sources("test_merge_from_paths_with_invalid_extends", &[
+            ("a.json", r#"{"$extends": 42}"#),
+            ("b.json", r#"{"$extends": "missing.json"}"#),
+        ]);
+        assert!(from_paths(vec![sources[0].clone()]).is_err());
+        assert!(from_paths(vec![sources[1].clone()]).is_err());
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_type_conflicts() {
+        let sources = write_sources("test_merge_from_paths_with_type_conflicts", &[
+            ("defaults.json", r#"{"model_details": {"owners": [{"name": "Jane"}], "version": {"name": "1"}}, "tags": null}"#),
+            ("model.json", r#"{"model_details": {"owners": "Jane", "version": {"name": 2}}, "tags": ["a"]}"#),
+        ]);
+        // non strict mode only warns
+        let result = from_paths(sources.clone()).unwrap();
+        assert_eq!(result["model_details"]["owners"], json!("Jane"));
+
+        let options = MergeOptions { strict: true, ..Default::default() };
+        let error = format!("{:?}", from_paths_with(sources.clone(), &options).unwrap_err());
+        assert!(error.contains(&format!("/model_details/owners: array from '{}' is replaced by string from '{}'", sources[0], sources[1])), "{}", error);
+        assert!(error.contains(&format!("/model_details/version/name: string from '{}' is replaced by number from '{}'", sources[0], sources[1])), "{}", error);
+        assert!(!error.contains("/tags"), "{}", error);
+    }
+
+    #[test]
+    fn test_merge_from_paths_strict_without_conflicts() {
+        let sources = write_sources("test_merge_from_paths_strict_without_conflicts", &[
+            ("defaults.json", r#"{"name": "default", "tags": ["a"], "version": {"name": "1"}}"#),
+            ("model.json", r#"{"name": "model", "tags": ["b"], "version": {"date": "2024-01-01"}}"#),
+        ]);
+        let options = MergeOptions { strict: true, ..Default::default() };
+        assert!(from_paths_with(sources, &options).is_ok());
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_embedded_files() {
+        let sources = write_sources("test_merge_from_paths_with_embedded_files", &[
+            ("roc.svg", r#"<svg xmlns="http://www.w3.org/2000/svg"/>"#),
+            ("defaults.yaml", "quantitative_analysis:\n  graphics:\n    collection:\n      - name: ROC\n        image:\n          $file: roc.svg\n"),
+        ]);
+        let result = from_paths(sources[1..].to_vec()).unwrap();
+        let image = result["quantitative_analysis"]["graphics"]["collection"][0]["image"].as_str().unwrap();
+        assert_eq!(image, "data:image/svg+xml;base64,PHN2ZyB4bWxucz0iaHR0cDovL3d3dy53My5vcmcvMjAwMC9zdmciLz4=");
+    }
+
+    #[test]
+    fn test_merge_from_paths_with_interpolation() {
+        let sources = write_sources("test_merge_from_paths_with_interpolation", &[
+            ("defaults.json", r#"{"model_details": {"name": "Unnamed", "documentation": "${/model_details/name} is documented in ${config:project_dir}"}}"#),
+            ("model.json", r#"{"model_details": {"name": "Model", "citation": "$${/model_details/name}"}}"#),
+        ]);
+        let options = MergeOptions {
+            interpolate: true,
+            variables: BTreeMap::from([("project_dir".to_string(), "cards".to_string())]),
+            ..Default::default()
+        };
+        let result = from_paths_with(sources.clone(), &options).unwrap();
+        assert_eq!(result["model_details"]["documentation"], json!("Model is documented in cards"));
+        assert_eq!(result["model_details"]["citation"], json!("${/model_details/name}"));
+        // the config variable is not set
+        let result = from_paths_with(sources.clone(), &MergeOptions { interpolate: true, ..Default::default() }).unwrap();
+        assert_eq!(result["model_details"]["documentation"], json!("Model is documented in ${config:project_dir}"));
+        assert!(from_paths_with(sources.clone(), &MergeOptions { interpolate: true, strict: true, ..Default::default() }).is_err());
+        // references are kept as written without interpolation
+        let result = from_paths(sources).unwrap();
+        assert_eq!(result["model_details"]["documentation"], json!("${/model_details/name} is documented in ${config:project_dir}"));
+        assert_eq!(result["model_details"]["citation"], json!("$${/model_details/name}"));
+    }
+
+    #[test]
+    fn test_merge_from_strings_with_format() {
+        let options = MergeOptions { format: Some(DataFormat::Yaml), ..Default::default() };
+        let result = from_strings_with(vec!["a: 1".to_string(), "b: [x]".to_string()], &options).unwrap();
+        assert_eq!(result, json!({"a": 1, "b": ["x"]}));
+    }
+
+    #[test]
+    fn test_provenance_tracks_winning_and_shadowed_layers() {
+        let sources = write_sources("test_provenance_tracks_layers", &[
+            ("defaults.json", r#"{"model_details": {"name": "default", "license": "MIT"}, "tags": ["a"]}"#),
+            ("usecase.json", r#"{"model_details": {"name": "usecase"}}"#),
+            ("model.json", r#"{"model_details": {"name": "model"}, "tags": ["b", "c"]}"#),
+        ]);
+        let (merged, provenance) = from_paths_with_provenance(sources.clone(), &MergeOptions::default()).unwrap();
+        assert_eq!(merged["model_details"]["name"], json!("model"));
+
+        let name = &provenance["/model_details/name"];
+        assert_eq!(name.source, sources[2]);
+        assert_eq!(name.shadowed, vec![
+            Shadowed { source: sources[0].clone(), value: json!("default") },
+            Shadowed { source: sources[1].clone(), value: json!("usecase") },
+        ]);
+        assert_eq!(provenance["/model_details/license"].source, sources[0]);
+        assert_eq!(provenance["/tags/1"].source, sources[2]);
+        assert_eq!(provenance.len(), leaves(&merged).len());
+    }
+
+    #[test]
+    fn test_provenance_with_array_strategies() {
+        let sources = write_sources("test_provenance_with_array_strategies", &[
+            ("defaults.json", r#"{"risks": [{"name": "bias"}], "refs": ["a"]}"#),
+            ("model.json", r#"{"risks": [{"name": "drift"}], "refs": ["b"]}"#),
+        ]);
+        let mut options = MergeOptions::default();
+        options.add_array_strategy("/risks=append").unwrap();
+        options.add_array_strategy("/refs=prepend").unwrap();
+        let (merged, provenance) = from_paths_with_provenance(sources.clone(), &options).unwrap();
+        assert_eq!(merged, json!({"risks": [{"name": "bias"}, {"name": "drift"}], "refs": ["b", "a"]}));
+        assert_eq!(provenance["/risks/0/name"].source, sources[0]);
+        assert_eq!(provenance["/risks/1/name"].source, sources[1]);
+        assert_eq!(provenance["/refs/0"].source, sources[1]);
+        assert_eq!(provenance["/refs/1"].source, sources[0]);
+    }
+
+    #[test]
+    fn test_provenance_with_patch_layers() {
+        let sources = write_sources("test_provenance_with_patch_layers", &[
+            ("defaults.json", r#"{"name": "default", "version": "1", "license": "MIT"}"#),
+            ("fix.patch.json", r#"[{"op": "replace", "path": "/version", "value": "2"}, {"op": "remove", "path": "/license"}]"#),
+        ]);
+        let (_, provenance) = from_paths_with_provenance(sources.clone(), &MergeOptions::default()).unwrap();
+        assert_eq!(provenance["/name"].source, sources[0]);
+        assert_eq!(provenance["/version"].source, sources[1]);
+        assert_eq!(provenance["/version"].shadowed, vec![Shadowed { source: sources[0].clone(), value: json!("1") }]);
+        assert!(!provenance.contains_key("/license"));
+    }
+
+    fn options(specs: &[&str]) -> MergeOptions {
+        let mut options = MergeOptions::default();
+        for spec in specs {
+            options.add_array_strategy(spec).expect("Invalid array strategy");
+        }
+        options
+    }
+
+    #[test]
+    fn test_merge_arrays_append_and_prepend() {
+        let mut a = json!({"a": [1, 2]});
+        merge_with(&mut a, json!({"a": [3]}), &options(&["append"]));
+        assert_eq!(a, json!({"a": [1, 2, 3]}));
+
+        let mut a = json!({"a": [1, 2]});
+        merge_with(&mut a, json!({"a": [3]}), &options(&["prepend"]));
+        assert_eq!(a, json!({"a": [3, 1, 2]}));
+    }
+
+    #[test]
+    fn test_merge_arrays_union() {
+        let mut a = json!({"a": ["slow", {"x": 1}]});
+        let b = json!({"a": [{"x": 1}, "large", "slow"]});
+        merge_with(&mut a, b, &options(&["union"]));
+        assert_eq!(a, json!({"a": ["slow", {"x": 1}, "large"]}));
+    }
+
+    #[test]
+    fn test_merge_arrays_by_key() {
+        let mut a = json!({"owners": [
+            {"name": "Jane", "contact": "jane@example.com"},
+            {"name": "John", "contact": "john@example.com"}
+        ]});
+        let b = json!({"owners": [
+            {"name": "John", "contact": "jd@example.com"},
+            {"name": "Max"},
+            "no object"
+        ]});
+        merge_with(&mut a, b, &options(&["merge-by-key:name"]));
+        let expected = json!({"owners": [
+            {"name": "Jane", "contact": "jane@example.com"},
+            {"name": "John", "contact": "jd@example.com"},
+            {"name": "Max"},
+            "no object"
+        ]});
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn test_merge_arrays_with_path_strategy() {
+        let mut a = json!({
+            "considerations": {"limitations": [{"description": "slow"}]},
+            "references": [{"reference": "a"}]
+        });
+        let b = json!({
+            "considerations": {"limitations": [{"description": "large"}]},
+            "references": [{"reference": "b"}]
+        });
+        merge_with(&mut a, b, &options(&["/considerations/limitations=append"]));
+        let expected = json!({
+            "considerations": {"limitations": [{"description": "slow"}, {"description": "large"}]},
+            "references": [{"reference": "b"}]
+        });
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn test_merge_deep_keeps_null() {
+        let mut a = json!({"a": 1, "b": {"c": 2}});
+        merge(&mut a, json!({"a": null, "b": {"c": null}}));
+        assert_eq!(a, json!({"a": null, "b": {"c": null}}));
+    }
+
+    #[test]
+    fn test_merge_patch_removes_null() {
+        let options = MergeOptions { mode: MergeMode::Patch, ..Default::default() };
+        let mut a = json!({
+            "model_details": {"name": "Model", "licenses": [{"identifier": "MIT"}]},
+            "considerations": {"users": ["everyone"]}
+        });
+        let b = json!({
+            "model_details": {"licenses": null, "version": {"name": "1.0", "date": null}},
+            "considerations": null,
+            "unknown": null
+        });
+        merge_with(&mut a, b, &options);
+        let expected = json!({
+            "model_details": {"name": "Model", "version": {"name": "1.0"}}
+        });
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn test_merge_patch_rfc7396_examples() {
+        // test cases taken from RFC 7396, Appendix A
+        let options = MergeOptions { mode: MergeMode::Patch, ..Default::default() };
+        let cases = vec![
+            (json!({"a":"b"}), json!({"a":"c"}), json!({"a":"c"})),
+            (json!({"a":"b"}), json!({"b":"c"}), json!({"a":"b","b":"c"})),
+            (json!({"a":"b"}), json!({"a":null}), json!({})),
+            (json!({"a":"b","b":"c"}), json!({"a":null}), json!({"b":"c"})),
+            (json!({"a":["b"]}), json!({"a":"c"}), json!({"a":"c"})),
+            (json!({"a":"c"}), json!({"a":["b"]}), json!({"a":["b"]})),
+            (json!({"a":{"b":"c"}}), json!({"a":{"b":"d","c":null}}), json!({"a":{"b":"d"}})),
+            (json!({"a":[{"b":"c"}]}), json!({"a":[1]}), json!({"a":[1]})),
+            (json!(["a","b"]), json!(["c","d"]), json!(["c","d"])),
+            (json!({"a":"b"}), json!(["c"]), json!(["c"])),
+            (json!({"a":"foo"}), json!(null), json!(null)),
+            (json!({"a":"foo"}), json!("bar"), json!("bar")),
+            (json!({"e":null}), json!({"a":1}), json!({"e":null,"a":1})),
+            (json!([1,2]), json!({"a":"b","c":null}), json!({"a":"b"})),
+            (json!({}), json!({"a":{"bb":{"ccc":null}}}), json!({"a":{"bb":{}}})),
+        ];
+        for (mut target, patch, expected) in cases {
+            merge_with(&mut target, patch, &options);
+            assert_eq!(target, expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_array_strategy() {
+        assert_eq!("replace".parse::<ArrayStrategy>().unwrap(), ArrayStrategy::Replace);
+        assert_eq!("merge-by-key:type".parse::<ArrayStrategy>().unwrap(), ArrayStrategy::MergeByKey("type".to_string()));
+        assert!("merge-by-key:".parse::<ArrayStrategy>().is_err());
+        assert!("shuffle".parse::<ArrayStrategy>().is_err());
+        assert!(MergeOptions::default().add_array_strategy("limitations=append").is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,248 @@
+//! Provenance tracking for merged modelcards.
+//!
+//! While merging, every leaf value of the result (strings, numbers, booleans, `null` and empty arrays or objects)
+//! is attributed to the source file which set it. Values of earlier layers that were overridden are kept as
+//! shadowed values, so a wrong value in a merged card can be traced back to the layer that introduced it.
+//! Array strategies like `append` or `merge-by-key` move items to other indexes, so the pointer of such a value
+//! in its source file is recorded as well.
+
+use std::{collections::{BTreeMap, BTreeSet, HashMap}, fmt};
+use serde_json::Value;
+
+use super::escape_pointer_token;
+
+/// A value of an earlier layer which was overridden by a later layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shadowed {
+    /// The source file of the overridden value.
+    pub source: String,
+    /// The overridden value.
+    pub value: Value,
+}
+
+/// Origin of a leaf value in a merged modelcard.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Origin {
+    /// The source file which set the value.
+    pub source: String,
+    /// Values of earlier layers at the same location, oldest first.
+    pub shadowed: Vec<Shadowed>,
+    /// JSON pointer of the value in the source file, if it differs from its pointer in the merged modelcard
+    /// (e.g. an appended array item).
+    pub source_pointer: Option<String>,
+}
+
+/// Map from JSON pointer of a leaf value to its origin.
+pub type Provenance = BTreeMap<String, Origin>;
+
+/// A value whose type was changed by a later layer, e.g. an object replaced by a string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeConflict {
+    /// JSON pointer of the value.
+    pub pointer: String,
+    /// The source file of the earlier value.
+    pub earlier_source: String,
+    /// The type of the earlier value.
+    pub earlier_type: &'static str,
+    /// The source file of the later value.
+    pub later_source: String,
+    /// The type of the later value.
+    pub later_type: &'static str,
+}
+
+impl fmt::Display for TypeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} from '{}' is replaced by {} from '{}'",
+            self.pointer, self.earlier_type, self.earlier_source, self.later_type, self.later_source)
+    }
+}
+
+/// Name of the JSON type of a value.
+pub fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Collect all leaf values of a JSON value together with their JSON pointers in document order.
+///
+/// Leaves are all scalar values as well as empty arrays and objects.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::merge::leaves;
+///
+/// let value = json!({"model_details": {"name": "Model", "owners": [{"name": "Jane"}]}});
+/// let pointers: Vec<String> = leaves(&value).into_iter().map(|(pointer, _)| pointer).collect();
+/// assert_eq!(pointers, vec!["/model_details/name", "/model_details/owners/0/name"]);
+/// ```
+///
+pub fn leaves(value: &Value) -> Vec<(String, &Value)> {
+    let mut result = vec![];
+    collect_leaves(value, String::new(), &mut result);
+    result
+}
+
+fn collect_leaves<'a>(value: &'a Value, pointer: String, result: &mut Vec<(String, &'a Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                collect_leaves(child, format!("{}/{}", pointer, escape_pointer_token(key)), result);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (index, child) in items.iter().enumerate() {
+                collect_leaves(child, format!("{}/{}", pointer, index), result);
+            }
+        }
+        _ => result.push((pointer, value)),
+    }
+}
+
+fn is_within(pointer: &str, prefix: &str) -> bool {
+    pointer == prefix || (pointer.starts_with(prefix) && pointer[prefix.len()..].starts_with('/'))
+}
+
+/// Records the origin of values while a single layer is merged.
+pub(crate) struct Recorder<'a> {
+    pub provenance: &'a mut Provenance,
+    pub conflicts: &'a mut Vec<TypeConflict>,
+    pub source: &'a str,
+    /// Array items of the layer merged to another index, by pointer in the merged modelcard.
+    moved: HashMap<String, String>,
+}
+
+impl<'a> Recorder<'a> {
+    pub fn new(provenance: &'a mut Provenance, conflicts: &'a mut Vec<TypeConflict>, source: &'a str) -> Self {
+        Recorder { provenance, conflicts, source, moved: HashMap::new() }
+    }
+
+    /// Record that the item at `index` of the array at `pointer` in the layer is merged to `merged_index`.
+    pub fn move_item(&mut self, pointer: &str, index: usize, merged_index: usize) {
+        let source = format!("{}/{}", self.source_pointer(pointer).unwrap_or_else(|| pointer.to_string()), index);
+        self.moved.insert(format!("{}/{}", pointer, merged_index), source);
+    }
+
+    /// The pointer in the layer of the value at `pointer` in the merged modelcard, if it differs.
+    fn source_pointer(&self, pointer: &str) -> Option<String> {
+        self.moved.iter()
+            .filter(|(merged, _)| is_within(pointer, merged))
+            .max_by_key(|(merged, _)| merged.len())
+            .map(|(merged, source)| format!("{}{}", source, &pointer[merged.len()..]))
+            .filter(|source| source != pointer)
+    }
+
+    /// Remove and return all entries at or below the given pointer.
+    fn take(&mut self, pointer: &str) -> Provenance {
+        let keys: Vec<String> = self.provenance.keys()
+            .filter(|key| is_within(key, pointer))
+            .cloned()
+            .collect();
+        keys.into_iter()
+            .filter_map(|key| self.provenance.remove(&key).map(|origin| (key, origin)))
+            .collect()
+    }
+
+    /// Record a type conflict if the value at `pointer` changes its type from `old` to `new`.
+    /// 
+    /// `null` is treated as placeholder which is compatible with any type.
+    pub fn check_type(&mut self, pointer: &str, old: &Value, new: &Value) {
+        if old.is_null() || new.is_null() || type_name(old) == type_name(new) {
+            return;
+        }
+        let earlier = self.provenance.iter()
+            .find(|(key, _)| is_within(key, pointer))
+            .map(|(_, origin)| origin.source.clone());
+        if let Some(earlier_source) = earlier {
+            self.conflicts.push(TypeConflict {
+                pointer: pointer.to_string(),
+                earlier_source,
+                earlier_type: type_name(old),
+                later_source: self.source.to_string(),
+                later_type: type_name(new),
+            });
+        }
+    }
+
+    /// Record that the value at `pointer` was replaced from `old` to `new`.
+    pub fn assign(&mut self, pointer: &str, old: &Value, new: &Value) {
+        self.check_type(pointer, old, new);
+        let previous = self.take(pointer);
+        for (leaf, _) in leaves(new) {
+            let leaf = format!("{}{}", pointer, leaf);
+            let mut origin = Origin { source: self.source.to_string(), source_pointer: self.source_pointer(&leaf), ..Default::default() };
+            if let Some(prev) = previous.get(&leaf) {
+                origin.shadowed = prev.shadowed.clone();
+                if let Some(value) = old.pointer(&leaf[pointer.len()..]) {
+                    origin.shadowed.push(Shadowed { source: prev.source.clone(), value: value.clone() });
+                }
+            } else if leaf == pointer {
+                // a whole object or array was replaced by a single value
+                let sources: BTreeSet<&String> = previous.values().map(|prev| &prev.source).collect();
+                for source in sources {
+                    origin.shadowed.push(Shadowed { source: source.clone(), value: old.clone() });
+                }
+            }
+            self.provenance.insert(leaf, origin);
+        }
+    }
+
+    /// Record that the value at `pointer` was removed.
+    pub fn remove(&mut self, pointer: &str) {
+        self.take(pointer);
+    }
+
+    /// Record that the empty object or array at `pointer` receives children and is no longer a leaf.
+    pub fn expand(&mut self, pointer: &str) {
+        self.provenance.remove(pointer);
+    }
+
+    /// Record that all entries of the array at `pointer` moved back by `offset` positions.
+    pub fn shift(&mut self, pointer: &str, offset: usize) {
+        let prefix = format!("{}/", pointer);
+        let moved = self.take(pointer);
+        for (key, mut origin) in moved {
+            let shifted = match key.strip_prefix(&prefix) {
+                Some(rest) => {
+                    let (index, tail) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+                    match index.parse::<usize>() {
+                        Ok(index) => format!("{}{}{}", prefix, index + offset, tail),
+                        Err(_) => key.clone(),
+                    }
+                }
+                None => key.clone(),
+            };
+            if shifted != key && origin.source_pointer.is_none() {
+                origin.source_pointer = Some(key);
+            }
+            self.provenance.insert(shifted, origin);
+        }
+    }
+
+    /// Record all leaves that differ between `before` and `after`, used for layers that are not merged recursively.
+    pub fn diff(&mut self, before: &Value, after: &Value) {
+        let old_leaves: BTreeMap<String, &Value> = leaves(before).into_iter().collect();
+        let mut next = Provenance::new();
+        for (pointer, value) in leaves(after) {
+            let previous = self.provenance.get(&pointer);
+            let origin = match (old_leaves.get(&pointer), previous) {
+                (Some(old), Some(origin)) if *old == value => origin.clone(),
+                (Some(old), Some(origin)) => {
+                    let mut shadowed = origin.shadowed.clone();
+                    shadowed.push(Shadowed { source: origin.source.clone(), value: (*old).clone() });
+                    Origin { source: self.source.to_string(), shadowed, ..Default::default() }
+                }
+                _ => Origin { source: self.source.to_string(), ..Default::default() },
+            };
+            next.insert(pointer, origin);
+        }
+        *self.provenance = next;
+    }
+}
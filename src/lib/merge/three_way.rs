@@ -0,0 +1,166 @@
+//! Three-way merge of modelcards.
+//!
+//! Two modelcards (ours and theirs) derived from a common base are merged by applying the changes of both sides
+//! to the base. A change made on only one side is taken over, objects are merged key by key and arrays of the same
+//! length index by index. When both sides changed the same value differently, a [`Conflict`] is reported.
+
+use std::fmt;
+use serde_json::{Map, Value};
+
+use super::escape_pointer_token;
+
+/// Key of the structured conflict markers written by [`three_way`].
+pub const CONFLICT_KEY: &str = "$conflict";
+
+/// A value changed differently by both sides of a three-way merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// JSON pointer of the value.
+    pub pointer: String,
+    /// The value in the base, `None` if it did not exist.
+    pub base: Option<Value>,
+    /// The value in ours, `None` if it was removed.
+    pub ours: Option<Value>,
+    /// The value in theirs, `None` if it was removed.
+    pub theirs: Option<Value>,
+}
+
+impl Conflict {
+    /// The structured conflict marker written in place of the conflicting value.
+    pub fn marker(&self) -> Value {
+        let mut sides = Map::new();
+        for (side, value) in [("base", &self.base), ("ours", &self.ours), ("theirs", &self.theirs)] {
+            sides.insert(side.to_string(), value.clone().unwrap_or(Value::Null));
+        }
+        let mut marker = Map::new();
+        marker.insert(CONFLICT_KEY.to_string(), Value::Object(sides));
+        Value::Object(marker)
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<Value>| value.as_ref().map_or("(missing)".to_string(), |value| value.to_string());
+        write!(f, "{}: ours {}, theirs {} (base {})", self.pointer, show(&self.ours), show(&self.theirs), show(&self.base))
+    }
+}
+
+/// Merge the changes of `ours` and `theirs` relative to their common `base`.
+///
+/// Returns the merged modelcard and all conflicts. Conflicting values keep the value of ours, unless `markers`
+/// is set, in which case they are replaced by a conflict marker `{"$conflict": {"base": ..., "ours": ..., "theirs": ...}}`.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::merge::three_way;
+///
+/// let base = json!({"description": "Draft", "metrics": {"accuracy": 0.8}});
+/// let ours = json!({"description": "Final description", "metrics": {"accuracy": 0.8}});
+/// let theirs = json!({"description": "Draft", "metrics": {"accuracy": 0.9}});
+/// let (merged, conflicts) = three_way(&base, &ours, &theirs, false);
+/// assert_eq!(merged, json!({"description": "Final description", "metrics": {"accuracy": 0.9}}));
+/// assert!(conflicts.is_empty());
+/// ```
+///
+pub fn three_way(base: &Value, ours: &Value, theirs: &Value, markers: bool) -> (Value, Vec<Conflict>) {
+    let mut conflicts = vec![];
+    let merged = merge3(Some(base), Some(ours), Some(theirs), "", markers, &mut conflicts);
+    (merged.unwrap_or(Value::Null), conflicts)
+}
+
+fn merge3(base: Option<&Value>, ours: Option<&Value>, theirs: Option<&Value>, pointer: &str, markers: bool, conflicts: &mut Vec<Conflict>) -> Option<Value> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+    match (base, ours, theirs) {
+        (None | Some(Value::Object(_)), Some(Value::Object(o)), Some(Value::Object(t))) => {
+            let empty = Map::new();
+            let b = base.and_then(Value::as_object).unwrap_or(&empty);
+            let mut merged = Map::new();
+            let keys = o.keys().chain(t.keys().filter(|key| !o.contains_key(*key)));
+            for key in keys {
+                let child = format!("{}/{}", pointer, escape_pointer_token(key));
+                if let Some(value) = merge3(b.get(key), o.get(key), t.get(key), &child, markers, conflicts) {
+                    merged.insert(key.clone(), value);
+                }
+            }
+            Some(Value::Object(merged))
+        }
+        (Some(Value::Array(b)), Some(Value::Array(o)), Some(Value::Array(t))) if b.len() == o.len() && o.len() == t.len() => {
+            let merged = (0..o.len())
+                .map(|index| {
+                    let child = format!("{}/{}", pointer, index);
+                    merge3(Some(&b[index]), Some(&o[index]), Some(&t[index]), &child, markers, conflicts).unwrap_or(Value::Null)
+                })
+                .collect();
+            Some(Value::Array(merged))
+        }
+        _ => {
+            let conflict = Conflict {
+                pointer: pointer.to_string(),
+                base: base.cloned(),
+                ours: ours.cloned(),
+                theirs: theirs.cloned(),
+            };
+            let result = if markers { Some(conflict.marker()) } else { ours.cloned() };
+            conflicts.push(conflict);
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_non_conflicting_changes() {
+        let base = json!({"name": "model", "owners": [{"name": "Jane"}], "metrics": [{"type": "accuracy", "value": 0.8}], "old": 1});
+        let ours = json!({"name": "model", "owners": [{"name": "Jane Doe"}], "metrics": [{"type": "accuracy", "value": 0.8}], "notes": "prose"});
+        let theirs = json!({"name": "model", "owners": [{"name": "Jane"}], "metrics": [{"type": "accuracy", "value": 0.9}], "old": 1, "new": true});
+        let (merged, conflicts) = three_way(&base, &ours, &theirs, false);
+        assert!(conflicts.is_empty(), "{:?}", conflicts);
+        assert_eq!(merged, json!({
+            "name": "model",
+            "owners": [{"name": "Jane Doe"}],
+            "metrics": [{"type": "accuracy", "value": 0.9}],
+            "notes": "prose",
+            "new": true
+        }));
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let base = json!({"description": "draft", "limitations": ["slow"], "removed": "x"});
+        let ours = json!({"description": "ours", "limitations": ["slow", "large"]});
+        let theirs = json!({"description": "theirs", "limitations": ["fast"], "removed": "y"});
+        let (merged, conflicts) = three_way(&base, &ours, &theirs, false);
+        let pointers: Vec<&str> = conflicts.iter().map(|conflict| conflict.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/description", "/limitations", "/removed"]);
+        assert_eq!(merged, ours);
+        assert_eq!(conflicts[2].to_string(), r#"/removed: ours (missing), theirs "y" (base "x")"#);
+    }
+
+    #[test]
+    fn test_conflict_markers() {
+        let base = json!({"model_details": {"name": "base"}});
+        let ours = json!({"model_details": {"name": "ours"}});
+        let theirs = json!({"model_details": {"name": "theirs"}});
+        let (merged, conflicts) = three_way(&base, &ours, &theirs, true);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(merged, json!({"model_details": {"name": {"$conflict": {"base": "base", "ours": "ours", "theirs": "theirs"}}}}));
+    }
+
+    #[test]
+    fn test_added_on_both_sides() {
+        let (merged, conflicts) = three_way(&json!({}), &json!({"a": {"x": 1}}), &json!({"a": {"y": 2}}), false);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, json!({"a": {"x": 1, "y": 2}}));
+    }
+}
@@ -0,0 +1,533 @@
+//! # Migrate
+//!
+//! Upgrade model cards to a newer version of their schema with declarative migrations.
+//!
+//! A [`Migration`] upgrades a model card from one `schema_version` to the next with a list of [`Step`]s,
+//! which address fields with JSON pointers ([RFC 6901](https://www.rfc-editor.org/rfc/rfc6901)):
+//!
+//! - `rename` - Rename a field, keeping it in place (`{"op": "rename", "path": "/model_details/owner", "to": "owners"}`).
+//! - `move` - Move a field to another location, `-` appends to an array (`{"op": "move", "from": "/license", "to": "/model_details/licenses/-"}`).
+//! - `split` - Move the fields of an object to separate locations (`{"op": "split", "path": "/data", "into": [{"key": "train", "to": "/datasets/-"}]}`).
+//! - `delete` - Remove a field (`{"op": "delete", "path": "/model_details/path"}`).
+//! - `wrap` - Wrap a value in an object under a key (`{"op": "wrap", "path": "/model_details/references/*", "key": "reference"}`).
+//!
+//! The `*` token of `rename`, `delete` and `wrap` paths matches all fields of an object or items of an array.
+//! Steps whose field does not exist are skipped and `wrap` leaves objects unchanged, so partially migrated
+//! cards can be migrated again. Intermediate objects and arrays of target locations are created as needed.
+//!
+//! Migrations are chained by their versions: a card on version `0.0.2` is migrated by the migration from
+//! `0.0.2`, then by the migration from its target version, and so on. The migrations of the Google model card
+//! schema are bundled (see [`builtin_migrations`]), custom migrations are loaded with [`load_migrations`].
+//!
+//! ## Functions
+//!
+//! The module provides the following functions:
+//!
+//! - `builtin_migrations() -> &'static [Migration]` - The migrations of the Google model card schema.
+//! - `load_migrations(path: &Path) -> Result<Vec<Migration>>` - Load migrations from a JSON, YAML or TOML file.
+//! - `migrate(modelcard: &mut Value, migrations: &[Migration], target: Option<&str>) -> Result<Vec<&Migration>>` - Migrate a model card.
+//! - `schema_version(modelcard: &Value) -> Option<String>` - The `schema_version` of a model card.
+//! - `latest_version(migrations: &[Migration]) -> Option<&str>` - The version the migrations lead to.
+//! - `check_version(modelcard: &Value, migrations: &[Migration]) -> Option<ValidationEntry>` - Warn if a model card is on an outdated version.
+//!
+//! ## Errors
+//!
+//! The functions will return an error if a migration file is malformed, a model card has no `schema_version`,
+//! there is no chain of migrations to the target version or a step would overwrite an existing field.
+//! The anyhow crate is used for error handling.
+//!
+
+use std::{path::Path, sync::OnceLock};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    assets,
+    utils::load_json_file,
+    validate::{Severity, ValidationEntry}
+};
+
+/// Pointer of the schema version of a model card.
+const VERSION_POINTER: &str = "/schema_version";
+/// Keyword of the validation entry of an outdated schema version.
+const KEYWORD: &str = "schema-version";
+
+/// A declarative migration step.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Step {
+    /// Rename the field at `path` to `to`, keeping it in its parent.
+    Rename { path: String, to: String },
+    /// Move the field at `from` to the location `to`.
+    Move { from: String, to: String },
+    /// Move the fields of the object at `path` to separate locations, removing the object if no fields are left.
+    Split { path: String, into: Vec<SplitTarget> },
+    /// Remove the field at `path`.
+    Delete { path: String },
+    /// Replace the value at `path` by an object holding it under `key`, unless it is an object.
+    Wrap { path: String, key: String },
+}
+
+/// The location a field of a split object is moved to.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SplitTarget {
+    /// The field of the split object.
+    pub key: String,
+    /// The location the field is moved to.
+    pub to: String,
+}
+
+/// A migration from one schema version to the next.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Migration {
+    /// The schema version migrated from.
+    pub from: String,
+    /// The schema version migrated to.
+    pub to: String,
+    /// What changed between the versions.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The steps applied in order.
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+impl Step {
+    /// Apply the step to a model card.
+    ///
+    /// ## Errors
+    ///
+    /// The function will return an error if a pointer is malformed or the step would overwrite an existing field.
+    /// The model card may be changed partially then, [`Migration::apply`] leaves it unchanged instead.
+    pub fn apply(&self, modelcard: &mut Value) -> Result<()> {
+        match self {
+            Step::Rename { path, to } => {
+                for tokens in matches(modelcard, &parse_pointer(path)?) {
+                    let value = take(modelcard, &tokens).expect("matched field exists");
+                    let mut target = tokens[..tokens.len() - 1].to_vec();
+                    target.push(to.clone());
+                    put(modelcard, &target, value).with_context(|| format!("Could not rename {} to '{}'", pointer_of(&tokens), to))?;
+                }
+            }
+            Step::Move { from, to } => {
+                let tokens = parse_pointer(from)?;
+                if tokens.iter().any(|token| token == "*") {
+                    bail!("Wildcards are not supported in the source of a move: {}", from);
+                }
+                if let Some(value) = take(modelcard, &tokens) {
+                    put(modelcard, &parse_pointer(to)?, value).with_context(|| format!("Could not move {} to {}", from, to))?;
+                }
+            }
+            Step::Split { path, into } => {
+                let tokens = parse_pointer(path)?;
+                if !modelcard.pointer(&pointer_of(&tokens)).is_some_and(Value::is_object) {
+                    return Ok(());
+                }
+                let mut fields = match take(modelcard, &tokens) {
+                    Some(Value::Object(fields)) => fields,
+                    _ => unreachable!("split field is an object"),
+                };
+                for target in into {
+                    if let Some(value) = fields.remove(&target.key) {
+                        put(modelcard, &parse_pointer(&target.to)?, value).with_context(|| format!("Could not split {}/{} to {}", path, target.key, target.to))?;
+                    }
+                }
+                if !fields.is_empty() {
+                    let left: Vec<&str> = fields.keys().map(String::as_str).collect();
+                    put(modelcard, &tokens, Value::Object(fields.clone()))
+                        .with_context(|| format!("Could not keep the fields {} of {}", left.join(", "), path))?;
+                }
+            }
+            Step::Delete { path } => {
+                // remove array items from the back, so the indices of the remaining matches stay valid
+                for tokens in matches(modelcard, &parse_pointer(path)?).into_iter().rev() {
+                    take(modelcard, &tokens);
+                }
+            }
+            Step::Wrap { path, key } => {
+                for tokens in matches(modelcard, &parse_pointer(path)?) {
+                    let value = modelcard.pointer_mut(&pointer_of(&tokens)).expect("matched field exists");
+                    if !value.is_object() {
+                        let mut wrapper = Map::new();
+                        wrapper.insert(key.clone(), value.take());
+                        *value = Value::Object(wrapper);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Migration {
+    /// Apply the steps of the migration to a model card and set its `schema_version` to the target version.
+    ///
+    /// ## Errors
+    ///
+    /// The function will return an error if a step fails, the model card is left unchanged in that case.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use modelcards::migrate::{Migration, Step};
+    ///
+    /// let migration = Migration {
+    ///     from: "1.0".to_string(),
+    ///     to: "2.0".to_string(),
+    ///     description: None,
+    ///     steps: vec![Step::Rename { path: "/model_details/owner".to_string(), to: "owners".to_string() }],
+    /// };
+    /// let mut modelcard = json!({"schema_version": "1.0", "model_details": {"owner": [{"name": "Jane"}]}});
+    /// migration.apply(&mut modelcard).unwrap();
+    /// assert_eq!(modelcard, json!({"schema_version": "2.0", "model_details": {"owners": [{"name": "Jane"}]}}));
+    /// ```
+    pub fn apply(&self, modelcard: &mut Value) -> Result<()> {
+        let mut migrated = modelcard.clone();
+        for (index, step) in self.steps.iter().enumerate() {
+            step.apply(&mut migrated).with_context(|| format!("Step {} of the migration from {} to {} failed", index + 1, self.from, self.to))?;
+        }
+        if let Value::Object(fields) = &mut migrated {
+            fields.insert("schema_version".to_string(), Value::String(self.to.clone()));
+        }
+        *modelcard = migrated;
+        Ok(())
+    }
+}
+
+/// The migrations between the versions of the Google model card schema (see [`crate::assets::schema::get_migrations`]).
+pub fn builtin_migrations() -> &'static [Migration] {
+    static MIGRATIONS: OnceLock<Vec<Migration>> = OnceLock::new();
+    MIGRATIONS.get_or_init(|| serde_json::from_str(assets::schema::get_migrations()).expect("Bundled migrations are invalid"))
+}
+
+/// Load migrations from a JSON, YAML or TOML file.
+///
+/// The file contains a list of migrations, or an object with the list as `migrations` (as TOML has no top level arrays).
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use std::path::Path;
+/// use modelcards::migrate::load_migrations;
+///
+/// let migrations = load_migrations(Path::new("migrations.toml")).expect("Failed to load migrations");
+/// ```
+pub fn load_migrations(path: &Path) -> Result<Vec<Migration>> {
+    let mut migrations = load_json_file(path)?;
+    if let Some(list) = migrations.get_mut("migrations") {
+        migrations = list.take();
+    }
+    serde_json::from_value(migrations).with_context(|| format!("Invalid migrations in file {}", path.display()))
+}
+
+/// The `schema_version` of a model card, numbers (e.g. `2.0` in YAML) are read as strings.
+pub fn schema_version(modelcard: &Value) -> Option<String> {
+    match modelcard.pointer(VERSION_POINTER)? {
+        Value::String(version) => Some(version.clone()),
+        Value::Number(version) => Some(version.to_string()),
+        _ => None,
+    }
+}
+
+/// The version the chain of migrations leads to, the target of the last migration.
+pub fn latest_version(migrations: &[Migration]) -> Option<&str> {
+    migrations.last().map(|migration| migration.to.as_str())
+}
+
+/// Migrate a model card to the target version (defaults to the latest version), returning the migrations applied.
+///
+/// A model card on the target version, or on a version no migration starts from if no target is given,
+/// is left unchanged.
+///
+/// ## Errors
+///
+/// The function will return an error if the model card has no `schema_version`, there is no chain of migrations
+/// to the target version or a step fails.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::migrate::{builtin_migrations, migrate};
+///
+/// let mut modelcard = json!({"schema_version": "0.0.2", "model_details": {"name": "Model", "license": "MIT"}});
+/// let applied = migrate(&mut modelcard, builtin_migrations(), None).unwrap();
+/// assert_eq!(applied.len(), 1);
+/// assert_eq!(modelcard["schema_version"], "2.0");
+/// assert_eq!(modelcard["model_details"]["licenses"], json!([{"identifier": "MIT"}]));
+/// ```
+pub fn migrate<'a>(modelcard: &mut Value, migrations: &'a [Migration], target: Option<&str>) -> Result<Vec<&'a Migration>> {
+    let mut version = schema_version(modelcard).context("Modelcard has no schema_version")?;
+    let mut migrated = modelcard.clone();
+    let mut applied: Vec<&Migration> = vec![];
+    while target != Some(version.as_str()) {
+        let migration = match migrations.iter().find(|migration| migration.from == version) {
+            Some(migration) => migration,
+            None if target.is_none() => break,
+            None => bail!("No migration from schema version {} to {}", version, target.unwrap_or_default()),
+        };
+        if applied.iter().any(|other| other.from == migration.to) || applied.len() > migrations.len() {
+            bail!("Migrations from schema version {} form a cycle", migration.from);
+        }
+        migration.apply(&mut migrated)?;
+        version = migration.to.clone();
+        applied.push(migration);
+    }
+    *modelcard = migrated;
+    Ok(applied)
+}
+
+/// Check if a model card is on an outdated schema version, one a migration starts from.
+///
+/// The warning has the keyword `schema-version` and suggests the latest version.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::{migrate::{builtin_migrations, check_version}, validate::Severity};
+///
+/// let entry = check_version(&json!({"schema_version": "0.0.2"}), builtin_migrations()).unwrap();
+/// assert_eq!(entry.severity, Severity::Warning);
+/// assert_eq!(entry.suggestion.as_deref(), Some("2.0"));
+/// assert!(check_version(&json!({"schema_version": "2.0"}), builtin_migrations()).is_none());
+/// ```
+pub fn check_version(modelcard: &Value, migrations: &[Migration]) -> Option<ValidationEntry> {
+    let version = schema_version(modelcard)?;
+    migrations.iter().find(|migration| migration.from == version)?;
+    let latest = latest_version(migrations)?;
+    Some(ValidationEntry {
+        pointer: VERSION_POINTER.to_string(),
+        keyword: KEYWORD.to_string(),
+        severity: Severity::Warning,
+        message: format!("schema version '{}' is outdated, the latest version is '{}' (run 'modelcards migrate' to upgrade)", version, latest),
+        suggestion: Some(latest.to_string()),
+        ..Default::default()
+    })
+}
+
+/// Split a JSON pointer into its unescaped tokens.
+fn parse_pointer(pointer: &str) -> Result<Vec<String>> {
+    if pointer.is_empty() {
+        bail!("Migration steps can not address the whole modelcard");
+    }
+    let Some(pointer) = pointer.strip_prefix('/') else {
+        bail!("Invalid JSON pointer '{}', it must start with '/'", pointer);
+    };
+    Ok(pointer.split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+fn pointer_of(tokens: &[String]) -> String {
+    tokens.iter().map(|token| format!("/{}", token.replace('~', "~0").replace('/', "~1"))).collect()
+}
+
+/// The existing fields matching the tokens, `*` matches all fields of an object or items of an array.
+fn matches(value: &Value, tokens: &[String]) -> Vec<Vec<String>> {
+    let Some((token, rest)) = tokens.split_first() else {
+        return vec![vec![]];
+    };
+    let children: Vec<(String, &Value)> = match (value, token.as_str()) {
+        (Value::Object(fields), "*") => fields.iter().map(|(key, child)| (key.clone(), child)).collect(),
+        (Value::Array(items), "*") => items.iter().enumerate().map(|(index, child)| (index.to_string(), child)).collect(),
+        (Value::Object(fields), key) => fields.get(key).map(|child| (key.to_string(), child)).into_iter().collect(),
+        (Value::Array(items), index) => index.parse::<usize>().ok()
+            .and_then(|i| items.get(i).map(|child| (index.to_string(), child)))
+            .into_iter().collect(),
+        _ => vec![],
+    };
+    let mut found = vec![];
+    for (token, child) in children {
+        for mut path in matches(child, rest) {
+            path.insert(0, token.clone());
+            found.push(path);
+        }
+    }
+    found
+}
+
+/// Remove the field at the tokens, if it exists.
+fn take(value: &mut Value, tokens: &[String]) -> Option<Value> {
+    let (last, parent) = tokens.split_last()?;
+    match value.pointer_mut(&pointer_of(parent))? {
+        Value::Object(fields) => fields.remove(last),
+        Value::Array(items) => last.parse::<usize>().ok().filter(|index| *index < items.len()).map(|index| items.remove(index)),
+        _ => None,
+    }
+}
+
+/// Insert a value at the tokens, creating missing objects (or an array for `-`) along the way.
+fn put(value: &mut Value, tokens: &[String], new: Value) -> Result<()> {
+    let Some((token, rest)) = tokens.split_first() else {
+        bail!("Can not replace the whole modelcard");
+    };
+    if value.is_null() {
+        *value = if token == "-" { Value::Array(vec![]) } else { Value::Object(Map::new()) };
+    }
+    match value {
+        Value::Object(fields) => {
+            if rest.is_empty() {
+                if fields.contains_key(token) {
+                    bail!("Field '{}' already exists", token);
+                }
+                fields.insert(token.clone(), new);
+                return Ok(());
+            }
+            put(fields.entry(token.clone()).or_insert(Value::Null), rest, new)
+        }
+        Value::Array(items) => {
+            let index = match token.as_str() {
+                "-" => items.len(),
+                index => index.parse::<usize>().ok().filter(|index| *index <= items.len())
+                    .with_context(|| format!("Invalid array index '{}'", index))?,
+            };
+            if rest.is_empty() {
+                items.insert(index, new);
+                return Ok(());
+            }
+            if index == items.len() {
+                items.push(Value::Null);
+            }
+            put(&mut items[index], rest, new)
+        }
+        other => bail!("Can not insert '{}' into {}", token, other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::{utils::create_file, validate::{Profile, SchemaOptions}};
+    use std::{env::temp_dir, fs::{create_dir, remove_dir_all}, path::PathBuf};
+
+    fn get_temp_dir(path: &str, create: bool) -> PathBuf {
+        let mut dir = temp_dir();
+        dir.push(path);
+        if dir.exists() {
+            remove_dir_all(&dir).expect("Could not free test directory");
+        }
+        if create {
+            create_dir(&dir).expect("Could not create test directory");
+        }
+        dir
+    }
+
+    fn steps(steps: Value) -> Vec<Step> {
+        serde_json::from_value(steps).unwrap()
+    }
+
+    fn apply(modelcard: &mut Value, steps: &[Step]) -> Result<()> {
+        steps.iter().try_for_each(|step| step.apply(modelcard))
+    }
+
+    #[test]
+    fn test_steps() {
+        let mut modelcard = json!({
+            "model_details": {"owner": [{"name": "Jane", "mail": "jane@example.org"}, {"name": "Joe"}], "path": "/tmp"},
+            "data": {"train": {"name": "Train"}, "eval": {"name": "Eval"}, "notes": "none"},
+            "tags": ["audio", "speech"]
+        });
+        let migration = steps(json!([
+            {"op": "rename", "path": "/model_details/owner", "to": "owners"},
+            {"op": "rename", "path": "/model_details/owners/*/mail", "to": "contact"},
+            {"op": "delete", "path": "/model_details/path"},
+            {"op": "split", "path": "/data", "into": [{"key": "train", "to": "/datasets/-"}, {"key": "eval", "to": "/datasets/-"}]},
+            {"op": "move", "from": "/data/notes", "to": "/model_details/notes/text"},
+            {"op": "delete", "path": "/data"},
+            {"op": "wrap", "path": "/tags/*", "key": "name"},
+            {"op": "delete", "path": "/missing/*"}
+        ]));
+        apply(&mut modelcard, &migration).unwrap();
+        assert_eq!(modelcard, json!({
+            "model_details": {"owners": [{"name": "Jane", "contact": "jane@example.org"}, {"name": "Joe"}], "notes": {"text": "none"}},
+            "datasets": [{"name": "Train"}, {"name": "Eval"}],
+            "tags": [{"name": "audio"}, {"name": "speech"}]
+        }));
+        // a second run changes nothing
+        let migrated = modelcard.clone();
+        apply(&mut modelcard, &migration).unwrap();
+        assert_eq!(modelcard, migrated);
+    }
+
+    #[test]
+    fn test_steps_do_not_overwrite_fields() {
+        let modelcard = json!({"name": "Model", "title": "Model"});
+        assert!(apply(&mut modelcard.clone(), &steps(json!([{"op": "rename", "path": "/title", "to": "name"}]))).is_err());
+        assert!(apply(&mut modelcard.clone(), &steps(json!([{"op": "move", "from": "/title", "to": "/name/text"}]))).is_err());
+        assert!(apply(&mut modelcard.clone(), &steps(json!([{"op": "delete", "path": "title"}]))).is_err());
+        assert!(serde_json::from_value::<Step>(json!({"op": "copy", "from": "/a", "to": "/b"})).is_err());
+    }
+
+    #[test]
+    fn test_migrate_google_sample() {
+        let sample: Value = serde_json::from_str(assets::schema::get_sample()).unwrap();
+        let mut modelcard = json!({
+            "schema_version": "0.0.2",
+            "model_details": {
+                "name": sample["model_details"]["name"],
+                "license": "Apache-2.0",
+                "references": ["https://example.org/paper"],
+                "citation": "Doe et al. (2020)"
+            },
+            "model_parameters": {"data": {"train": {"name": "Train Set"}, "eval": {"name": "Eval Set"}}},
+            "considerations": {"users": ["Researchers"], "limitations": ["English only"]}
+        });
+        assert!(check_version(&modelcard, builtin_migrations()).is_some());
+        let applied = migrate(&mut modelcard, builtin_migrations(), Some("2.0")).unwrap();
+        assert_eq!(applied.len(), 1);
+        assert_eq!(modelcard["model_details"]["licenses"], json!([{"identifier": "Apache-2.0"}]));
+        assert_eq!(modelcard["model_details"]["references"], json!([{"reference": "https://example.org/paper"}]));
+        assert_eq!(modelcard["model_details"]["citations"], json!([{"citation": "Doe et al. (2020)"}]));
+        assert_eq!(modelcard["model_parameters"]["data"], json!([{"name": "Train Set"}, {"name": "Eval Set"}]));
+        assert_eq!(modelcard["considerations"]["users"], json!([{"description": "Researchers"}]));
+        assert!(check_version(&modelcard, builtin_migrations()).is_none());
+        let report = Profile::Google.validate(&modelcard, None, &SchemaOptions::default()).unwrap();
+        assert!(report.is_valid(), "{}", report);
+        assert!(!report.has_warnings(), "{}", report);
+
+        // the sample is on the latest version
+        let mut latest = sample.clone();
+        assert!(migrate(&mut latest, builtin_migrations(), None).unwrap().is_empty());
+        assert_eq!(latest, sample);
+        assert!(migrate(&mut latest, builtin_migrations(), Some("3.0")).is_err());
+        assert!(migrate(&mut json!({}), builtin_migrations(), None).is_err());
+    }
+
+    #[test]
+    fn test_migration_chain() {
+        let dir = get_temp_dir("test_migration_chain", true);
+        let file = dir.join("migrations.toml");
+        create_file(&file, r#"
+[[migrations]]
+from = "1"
+to = "2"
+steps = [{op = "rename", path = "/name", to = "title"}]
+
+[[migrations]]
+from = "2"
+to = "3"
+description = "titles are objects"
+steps = [{op = "wrap", path = "/title", key = "text"}]
+"#).unwrap();
+        let migrations = load_migrations(&file).unwrap();
+        assert_eq!(latest_version(&migrations), Some("3"));
+        let mut modelcard = json!({"schema_version": 1, "name": "Model"});
+        let applied = migrate(&mut modelcard, &migrations, None).unwrap();
+        assert_eq!(applied.iter().map(|migration| migration.to.as_str()).collect::<Vec<_>>(), vec!["2", "3"]);
+        assert_eq!(modelcard, json!({"schema_version": "3", "title": {"text": "Model"}}));
+
+        let mut modelcard = json!({"schema_version": "1", "name": "Model"});
+        migrate(&mut modelcard, &migrations, Some("2")).unwrap();
+        assert_eq!(modelcard, json!({"schema_version": "2", "title": "Model"}));
+
+        // a failing step leaves the modelcard unchanged
+        let mut modelcard = json!({"schema_version": "1", "name": "Model", "title": "Model"});
+        assert!(migrate(&mut modelcard, &migrations, None).is_err());
+        assert_eq!(modelcard["schema_version"], "1");
+        remove_dir_all(&dir).expect("Failed to remove test directory");
+    }
+}
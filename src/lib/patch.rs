@@ -0,0 +1,321 @@
+//! # Patch
+//!
+//! Apply JSON Patch documents ([RFC 6902](https://www.rfc-editor.org/rfc/rfc6902)) to JSON values.
+//!
+//! A JSON Patch document is an array of operations (`add`, `remove`, `replace`, `move`, `copy` and `test`),
+//! where each operation addresses its target location with a JSON pointer ([RFC 6901](https://www.rfc-editor.org/rfc/rfc6901)).
+//! In contrast to the recursive merge, patches allow surgical edits like inserting into the middle of an array
+//! or asserting preconditions with `test`.
+//!
+//! ## Functions
+//!
+//! The module provides the following functions:
+//!
+//! - `apply_patch(doc: &mut Value, patch: &Value) -> Result<()>` - Apply a JSON Patch document to a JSON value.
+//! - `is_patch_file(path: &str) -> bool` - Check if a file name denotes a JSON Patch file (`*.patch.json`, `*.patch.yaml`).
+//!
+//! ## Errors
+//!
+//! The functions will return an error if the patch document is malformed, a location does not exist or a `test` operation fails.
+//! Patches are applied atomically, so the value stays unchanged if any operation fails.
+//! The anyhow crate is used for error handling.
+//!
+
+use serde_json::Value;
+use anyhow::{bail, Context, Result};
+
+/// File name suffixes used to identify JSON Patch files.
+pub const PATCH_FILE_SUFFIXES: [&str; 3] = [".patch.json", ".patch.yaml", ".patch.yml"];
+
+/// Check if a file name denotes a JSON Patch file.
+/// 
+/// Patch files end with `.patch.json`, or `.patch.yaml`/`.patch.yml` for patches written in YAML.
+///
+/// ## Example
+///
+/// ```rust
+/// use modelcards::patch::is_patch_file;
+///
+/// assert!(is_patch_file("layers/metrics.patch.json"));
+/// assert!(is_patch_file("layers/metrics.patch.yaml"));
+/// assert!(!is_patch_file("layers/metrics.json"));
+/// ```
+///
+pub fn is_patch_file(path: &str) -> bool {
+    PATCH_FILE_SUFFIXES.iter().any(|suffix| path.ends_with(suffix))
+}
+
+/// Apply a JSON Patch document to a JSON value.
+///
+/// The operations are applied in order. If any operation fails, the value is left unchanged.
+///
+/// ## Arguments
+///
+/// - `doc` - A mutable reference to the JSON value to patch.
+/// - `patch` - The JSON Patch document (an array of operations).
+///
+/// ## Errors
+///
+/// The function will return an error if the patch is not a valid JSON Patch document, if a location does not exist or if a `test` operation fails.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::patch::apply_patch;
+///
+/// let mut doc = json!({"metrics": [{"type": "accuracy"}, {"type": "recall"}]});
+/// let patch = json!([
+///     {"op": "test", "path": "/metrics/0/type", "value": "accuracy"},
+///     {"op": "add", "path": "/metrics/1", "value": {"type": "precision"}}
+/// ]);
+/// apply_patch(&mut doc, &patch).unwrap();
+/// assert_eq!(doc, json!({"metrics": [{"type": "accuracy"}, {"type": "precision"}, {"type": "recall"}]}));
+/// ```
+///
+pub fn apply_patch(doc: &mut Value, patch: &Value) -> Result<()> {
+    let operations = match patch.as_array() {
+        Some(operations) => operations,
+        None => bail!("JSON Patch document must be an array of operations"),
+    };
+    let mut patched = doc.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        apply_operation(&mut patched, operation)
+            .with_context(|| format!("JSON Patch operation {} failed: {}", index, operation))?;
+    }
+    *doc = patched;
+    Ok(())
+}
+
+fn apply_operation(doc: &mut Value, operation: &Value) -> Result<()> {
+    let op = member_str(operation, "op")?;
+    let path = member_str(operation, "path")?;
+    match op {
+        "add" => add(doc, path, member_value(operation, "value")?.clone()),
+        "remove" => remove(doc, path).map(|_| ()),
+        "replace" => {
+            let value = member_value(operation, "value")?.clone();
+            match resolve_mut(doc, path)? {
+                Some(target) => {
+                    *target = value;
+                    Ok(())
+                }
+                None => bail!("Path '{}' does not exist", path),
+            }
+        }
+        "move" => {
+            let from = member_str(operation, "from")?;
+            if from == path {
+                return Ok(());
+            }
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                bail!("Can not move '{}' into one of its children '{}'", from, path);
+            }
+            let value = remove(doc, from)?;
+            add(doc, path, value)
+        }
+        "copy" => {
+            let from = member_str(operation, "from")?;
+            let value = match resolve_mut(doc, from)? {
+                Some(value) => value.clone(),
+                None => bail!("Path '{}' does not exist", from),
+            };
+            add(doc, path, value)
+        }
+        "test" => {
+            let expected = member_value(operation, "value")?;
+            match resolve_mut(doc, path)? {
+                Some(actual) if actual == expected => Ok(()),
+                Some(actual) => bail!("Test failed at '{}': expected {}, found {}", path, expected, actual),
+                None => bail!("Test failed: path '{}' does not exist", path),
+            }
+        }
+        other => bail!("Unknown operation '{}'", other),
+    }
+}
+
+fn member_value<'a>(operation: &'a Value, name: &str) -> Result<&'a Value> {
+    match operation.get(name) {
+        Some(value) => Ok(value),
+        None => bail!("Missing member '{}'", name),
+    }
+}
+
+fn member_str<'a>(operation: &'a Value, name: &str) -> Result<&'a str> {
+    match member_value(operation, name)?.as_str() {
+        Some(value) => Ok(value),
+        None => bail!("Member '{}' must be a string", name),
+    }
+}
+
+/// Split a JSON pointer into its unescaped reference tokens.
+pub(crate) fn parse_pointer(pointer: &str) -> Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    if !pointer.starts_with('/') {
+        bail!("Invalid JSON pointer '{}' (must start with '/')", pointer);
+    }
+    Ok(pointer[1..]
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn array_index(token: &str, len: usize, allow_end: bool) -> Result<usize> {
+    if allow_end && token == "-" {
+        return Ok(len);
+    }
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) || !token.bytes().all(|b| b.is_ascii_digit()) {
+        bail!("Invalid array index '{}'", token);
+    }
+    let index: usize = token.parse()?;
+    let max = if allow_end { len } else { len.saturating_sub(1) };
+    if index > max || (!allow_end && len == 0) {
+        bail!("Array index {} is out of bounds", index);
+    }
+    Ok(index)
+}
+
+fn resolve_mut<'a>(doc: &'a mut Value, pointer: &str) -> Result<Option<&'a mut Value>> {
+    let mut current = doc;
+    for token in parse_pointer(pointer)? {
+        current = match current {
+            Value::Object(map) => match map.get_mut(&token) {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+            Value::Array(items) => {
+                let len = items.len();
+                match array_index(&token, len, false) {
+                    Ok(index) => &mut items[index],
+                    Err(_) => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+    }
+    Ok(Some(current))
+}
+
+fn resolve_parent<'a>(doc: &'a mut Value, pointer: &str) -> Result<(&'a mut Value, String)> {
+    let mut tokens = parse_pointer(pointer)?;
+    let last = match tokens.pop() {
+        Some(last) => last,
+        None => bail!("Operation can not target the document root"),
+    };
+    let parent = tokens.iter()
+        .map(|token| format!("/{}", token.replace('~', "~0").replace('/', "~1")))
+        .collect::<String>();
+    match resolve_mut(doc, &parent)? {
+        Some(value) => Ok((value, last)),
+        None => bail!("Parent of path '{}' does not exist", pointer),
+    }
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<()> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, last) = resolve_parent(doc, path)?;
+    match parent {
+        Value::Object(map) => {
+            map.insert(last, value);
+        }
+        Value::Array(items) => {
+            let index = array_index(&last, items.len(), true)?;
+            items.insert(index, value);
+        }
+        _ => bail!("Parent of path '{}' is neither an object nor an array", path),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value> {
+    let (parent, last) = resolve_parent(doc, path)?;
+    match parent {
+        Value::Object(map) => match map.remove(&last) {
+            Some(value) => Ok(value),
+            None => bail!("Path '{}' does not exist", path),
+        },
+        Value::Array(items) => {
+            let index = array_index(&last, items.len(), false)?;
+            Ok(items.remove(index))
+        }
+        _ => bail!("Path '{}' does not exist", path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patched(mut doc: Value, patch: Value) -> Result<Value> {
+        apply_patch(&mut doc, &patch)?;
+        Ok(doc)
+    }
+
+    #[test]
+    fn test_add_and_insert_into_array() {
+        let doc = json!({"foo": ["bar", "baz"]});
+        let result = patched(doc, json!([
+            {"op": "add", "path": "/foo/1", "value": "qux"},
+            {"op": "add", "path": "/foo/-", "value": "end"},
+            {"op": "add", "path": "/new", "value": {"a": 1}}
+        ])).unwrap();
+        assert_eq!(result, json!({"foo": ["bar", "qux", "baz", "end"], "new": {"a": 1}}));
+    }
+
+    #[test]
+    fn test_remove_and_replace() {
+        let doc = json!({"baz": "qux", "foo": ["bar", "qux", "baz"]});
+        let result = patched(doc, json!([
+            {"op": "remove", "path": "/foo/1"},
+            {"op": "replace", "path": "/baz", "value": "boo"}
+        ])).unwrap();
+        assert_eq!(result, json!({"baz": "boo", "foo": ["bar", "baz"]}));
+    }
+
+    #[test]
+    fn test_move_and_copy() {
+        let doc = json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}});
+        let result = patched(doc, json!([
+            {"op": "move", "from": "/foo/waldo", "path": "/qux/thud"},
+            {"op": "copy", "from": "/foo/bar", "path": "/qux/bar"}
+        ])).unwrap();
+        assert_eq!(result, json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred", "bar": "baz"}}));
+    }
+
+    #[test]
+    fn test_escaped_pointer() {
+        let doc = json!({"a/b": {"m~n": 1}});
+        let result = patched(doc, json!([{"op": "replace", "path": "/a~1b/m~0n", "value": 2}])).unwrap();
+        assert_eq!(result, json!({"a/b": {"m~n": 2}}));
+    }
+
+    #[test]
+    fn test_failed_test_leaves_document_unchanged() {
+        let mut doc = json!({"name": "model", "version": "1"});
+        let patch = json!([
+            {"op": "replace", "path": "/name", "value": "other"},
+            {"op": "test", "path": "/version", "value": "2"}
+        ]);
+        assert!(apply_patch(&mut doc, &patch).is_err());
+        assert_eq!(doc, json!({"name": "model", "version": "1"}));
+    }
+
+    #[test]
+    fn test_invalid_operations() {
+        let doc = json!({"foo": ["bar"], "obj": {"a": 1}});
+        assert!(patched(doc.clone(), json!({"op": "add"})).is_err());
+        assert!(patched(doc.clone(), json!([{"op": "unknown", "path": "/foo"}])).is_err());
+        assert!(patched(doc.clone(), json!([{"op": "add", "path": "/missing/child", "value": 1}])).is_err());
+        assert!(patched(doc.clone(), json!([{"op": "add", "path": "/foo/5", "value": 1}])).is_err());
+        assert!(patched(doc.clone(), json!([{"op": "remove", "path": "/nothing"}])).is_err());
+        assert!(patched(doc.clone(), json!([{"op": "replace", "path": "/foo/01", "value": 1}])).is_err());
+        assert!(patched(doc.clone(), json!([{"op": "move", "from": "/obj", "path": "/obj/a"}])).is_err());
+        assert!(patched(doc, json!([{"op": "add", "path": "/foo/0"}])).is_err());
+    }
+}
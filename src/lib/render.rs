@@ -10,6 +10,14 @@
 //! - `render_template_valid(template: &Path, data: &Path, schema: &Path) -> Result<String>` - Render a template with a data file to String and validate against a schema.
 //! - `render_value_to_template(data: Value, template: Option<&Path>) -> Result<String>` - Render a template with a JSON object.
 //! 
+//! ## Filters
+//! 
+//! Besides the built-in filters of minijinja, templates can use:
+//! 
+//! - `spdx_name` - The full name of an SPDX license identifier from the bundled license list, e.g. `Apache License 2.0`.
+//!   Unknown identifiers and expressions are returned unchanged.
+//! - `spdx_url` - The URL of an SPDX license on spdx.org, an empty string for unknown identifiers.
+//! 
 //! ## Errors
 //! 
 //! The functions will return an error if the template, data, or schema file could not be found or if the JSON object does not validate against the schema.
@@ -21,6 +29,7 @@ use serde_json::Value;
 
 use std::{fs::read_to_string, ffi::OsStr, path::Path};
 use crate::{
+    spdx,
     utils::console,
     validate::check_against_schema
 };
@@ -61,7 +70,7 @@ pub fn render_template(template: &Path, data: &Path) -> Result<String> {
         bail!("Modelcard file '{}' does not exist.", data.display());
     }
 
-    let data = crate::utils::load_json_file(data)?;
+    let data = crate::merge::load_modelcard(data)?;
 
     render_value_to_template(data, Some(template))
 }
@@ -111,7 +120,7 @@ pub fn render_template_valid(template: &Path, data: &Path, schema: &Path) -> Res
         bail!("Project could not be validated!\n{:?}", e);
     }
 
-    let data = crate::utils::load_json_file(data)?;
+    let data = crate::merge::load_modelcard(data)?;
 
     render_value_to_template(data, Some(template))
 }
@@ -175,6 +184,7 @@ pub fn render_value_to_template(data: Value, template: Option<&Path>) -> Result<
     console::debug(&format!("Template: {}", template_name));
 
     let mut env = Environment::new();
+    add_filters(&mut env);
     env.add_template(template_name, template_content.as_str())?;
     let template = env.get_template(template_name)?;
     
@@ -187,6 +197,15 @@ pub fn render_value_to_template(data: Value, template: Option<&Path>) -> Result<
     }
 }
 
+/// Add the filters of the modelcards templates to the environment.
+fn add_filters(env: &mut Environment) {
+    env.add_filter("spdx_name", |id: String| -> String {
+        spdx::license(&id).map_or(id, |license| license.name.clone())
+    });
+    env.add_filter("spdx_url", |id: String| -> String {
+        spdx::license_url(&id).unwrap_or_default()
+    });
+}
 
 #[allow(dead_code)]
 #[doc(hidden)]
@@ -263,7 +282,7 @@ mod tests {
         );
 
         let missing_data_path = Path::new("nonexistent_data.json");
-        let result = render_template_valid(&template_path, &missing_data_path, &schema_path);
+        let result = render_template_valid(&template_path, missing_data_path, &schema_path);
         assert!(result.is_err());
     }
 
@@ -277,20 +296,29 @@ mod tests {
         );
 
         let schema_path = Path::new("invalid_schema.json");
-        let result = render_template_valid(&template_path, &data_path, &schema_path);
+        let result = render_template_valid(&template_path, &data_path, schema_path);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_render_value_to_template_with_default_template() {
         let content = crate::assets::schema::get_sample();
-        let data = serde_json::from_str(&content).unwrap();
+        let data = serde_json::from_str(content).unwrap();
         let result = render_value_to_template(data, None);
         assert!(result.is_ok());
         // Assuming the default template content is "Hello, {{ name }}!"
         //assert_eq!(result.unwrap(), "Hello, World!");
     }
 
+    #[test]
+    fn test_render_license_names_and_links() {
+        let mut data: Value = serde_json::from_str(crate::assets::schema::get_sample()).unwrap();
+        data["model_details"]["licenses"] = serde_json::json!([{"identifier": "Apache-2.0"}, {"identifier": "proprietary"}]);
+        let result = render_value_to_template(data, None).expect("Could not render template");
+        assert!(result.contains("* [Apache License 2.0](https://spdx.org/licenses/Apache-2.0.html)"), "{}", result);
+        assert!(result.contains("* proprietary"), "{}", result);
+    }
+
     #[test]
     fn test_render_value_to_template_with_custom_template() {
         let (template_path, data_path, _) = setup_test_environment(
@@ -0,0 +1,482 @@
+//! # SPDX
+//!
+//! Offline validation of [SPDX license expressions](https://spdx.github.io/spdx-spec/v2.3/SPDX-license-expressions/)
+//! against the bundled SPDX license list (see [`crate::assets::spdx`]).
+//!
+//! ## Functions
+//!
+//! The module provides the following functions:
+//!
+//! - `licenses() -> &'static [License]` - All licenses of the bundled list.
+//! - `license_list_version() -> &'static str` - The version of the bundled list, e.g. `3.27.0`.
+//! - `license(id: &str) -> Option<&'static License>` - Look up a license by its identifier.
+//! - `license_url(id: &str) -> Option<String>` - The URL of a license on spdx.org.
+//! - `suggest_license(id: &str) -> Option<&'static str>` - The identifier meant by a misspelled identifier or license name.
+//! - `validate_expression(expression: &str) -> Result<Vec<&'static License>, ExpressionError>` - Validate a license expression.
+//! - `check_licenses(modelcard: &Value) -> Vec<ValidationEntry>` - Check the license identifiers of a Google model card.
+//!
+//! ## Expressions
+//!
+//! An expression combines license identifiers (optionally followed by `+` and `WITH <exception>`) with `AND`
+//! and `OR` and parentheses, e.g. `(MIT OR Apache-2.0) AND CC-BY-4.0`. Identifiers are matched case-insensitively,
+//! the operators must be upper case. `LicenseRef-<id>` and `DocumentRef-<id>:LicenseRef-<id>` reference licenses
+//! not on the list; `NONE`, `NOASSERTION` and `proprietary` are accepted on their own.
+//!
+
+use std::{fmt, sync::OnceLock};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    assets,
+    utils::closest_match,
+    validate::{Severity, ValidationEntry}
+};
+
+/// Values accepted instead of a license expression.
+const SPECIAL_VALUES: [&str; 3] = ["NONE", "NOASSERTION", "proprietary"];
+/// Keyword of validation entries for license identifiers.
+const KEYWORD: &str = "spdx";
+
+/// A license of the SPDX license list.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct License {
+    /// The SPDX identifier, e.g. `Apache-2.0`.
+    #[serde(rename = "licenseId")]
+    pub id: String,
+    /// The full name, e.g. `Apache License 2.0`.
+    pub name: String,
+    /// Whether the license is approved by the Open Source Initiative.
+    #[serde(rename = "isOsiApproved")]
+    pub osi_approved: bool,
+    /// Whether the identifier is deprecated, e.g. `GPL-3.0` in favor of `GPL-3.0-only`.
+    #[serde(rename = "isDeprecatedLicenseId")]
+    pub deprecated: bool,
+}
+
+/// A license exception of the SPDX license list, used with `WITH`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Exception {
+    /// The SPDX identifier, e.g. `LLVM-exception`.
+    #[serde(rename = "licenseExceptionId")]
+    pub id: String,
+    /// Whether the identifier is deprecated.
+    #[serde(rename = "isDeprecatedLicenseId")]
+    pub deprecated: bool,
+}
+
+#[derive(Deserialize)]
+struct LicenseList {
+    #[serde(rename = "licenseListVersion")]
+    version: String,
+    licenses: Vec<License>,
+}
+
+#[derive(Deserialize)]
+struct ExceptionList {
+    exceptions: Vec<Exception>,
+}
+
+struct Lists {
+    version: String,
+    licenses: Vec<License>,
+    exceptions: Vec<Exception>,
+}
+
+fn list() -> &'static Lists {
+    static LIST: OnceLock<Lists> = OnceLock::new();
+    LIST.get_or_init(|| {
+        let licenses: LicenseList = serde_json::from_str(assets::spdx::get_licenses()).expect("Bundled SPDX license list is invalid");
+        let exceptions: ExceptionList = serde_json::from_str(assets::spdx::get_exceptions()).expect("Bundled SPDX exception list is invalid");
+        Lists { version: licenses.version, licenses: licenses.licenses, exceptions: exceptions.exceptions }
+    })
+}
+
+/// The version of the bundled SPDX license list, e.g. `3.27.0`.
+pub fn license_list_version() -> &'static str {
+    &list().version
+}
+
+/// All licenses of the bundled SPDX license list.
+pub fn licenses() -> &'static [License] {
+    &list().licenses
+}
+
+/// Look up a license by its identifier, ignoring case.
+///
+/// ## Example
+///
+/// ```rust
+/// use modelcards::spdx::license;
+///
+/// assert_eq!(license("apache-2.0").unwrap().name, "Apache License 2.0");
+/// assert!(license("Apache 2.0").is_none());
+/// ```
+///
+pub fn license(id: &str) -> Option<&'static License> {
+    licenses().iter().find(|license| license.id.eq_ignore_ascii_case(id))
+}
+
+/// Look up a license exception by its identifier, ignoring case.
+pub fn exception(id: &str) -> Option<&'static Exception> {
+    list().exceptions.iter().find(|exception| exception.id.eq_ignore_ascii_case(id))
+}
+
+/// The URL of a license on spdx.org, `None` if the license is not on the list.
+pub fn license_url(id: &str) -> Option<String> {
+    license(id).map(|license| format!("https://spdx.org/licenses/{}.html", license.id))
+}
+
+/// The identifier meant by a misspelled identifier or a license name.
+///
+/// Identifiers and names are compared ignoring case and punctuation first (so `Apache 2.0` and
+/// `MIT License` are found), then by edit distance. Deprecated identifiers are never suggested.
+///
+/// ## Example
+///
+/// ```rust
+/// use modelcards::spdx::suggest_license;
+///
+/// assert_eq!(suggest_license("Apache 2.0"), Some("Apache-2.0"));
+/// assert_eq!(suggest_license("MIT License"), Some("MIT"));
+/// assert_eq!(suggest_license("BSD-3-Clouse"), Some("BSD-3-Clause"));
+/// assert_eq!(suggest_license("GPL-3.0"), Some("GPL-3.0-only"));
+/// ```
+///
+pub fn suggest_license(id: &str) -> Option<&'static str> {
+    if let Some(replacement) = license(id).and_then(replacement_of) {
+        return Some(replacement);
+    }
+    let current = || licenses().iter().filter(|license| !license.deprecated);
+    let normalized = normalize(id);
+    current()
+        .find(|license| normalize(&license.id) == normalized || normalize(&license.name) == normalized)
+        .map(|license| license.id.as_str())
+        .or_else(|| closest_match(id, current().map(|license| license.id.as_str())))
+}
+
+/// The current identifier of a deprecated license, e.g. `GPL-2.0+` is `GPL-2.0-or-later`.
+fn replacement_of(license: &License) -> Option<&'static str> {
+    if !license.deprecated {
+        return None;
+    }
+    let replacement = match license.id.strip_suffix('+') {
+        Some(base) => format!("{}-or-later", base),
+        None => format!("{}-only", license.id),
+    };
+    self::license(&replacement).map(|license| license.id.as_str())
+}
+
+fn normalize(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric() || *c == '+').flat_map(char::to_lowercase).collect()
+}
+
+/// An invalid license expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionError {
+    /// Description of the problem.
+    pub message: String,
+    /// The identifier probably meant, if the problem is an unknown identifier.
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean '{}'?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// Validate a license expression against the bundled SPDX license list.
+///
+/// Returns the licenses referenced by the expression (without `LicenseRef`s).
+///
+/// ## Errors
+///
+/// The function returns an [`ExpressionError`] if the expression is malformed or contains an unknown
+/// license or exception identifier, with a suggestion for misspelled identifiers.
+///
+/// ## Example
+///
+/// ```rust
+/// use modelcards::spdx::validate_expression;
+///
+/// let licenses = validate_expression("(MIT OR Apache-2.0) AND LicenseRef-Internal").unwrap();
+/// assert_eq!(licenses.len(), 2);
+/// assert!(validate_expression("GPL-2.0-or-later WITH Classpath-exception-2.0").is_ok());
+///
+/// let error = validate_expression("Apache 2.0").unwrap_err();
+/// assert_eq!(error.suggestion.as_deref(), Some("Apache-2.0"));
+/// ```
+///
+pub fn validate_expression(expression: &str) -> Result<Vec<&'static License>, ExpressionError> {
+    let trimmed = expression.trim();
+    if SPECIAL_VALUES.iter().any(|value| value.eq_ignore_ascii_case(trimmed)) {
+        return Ok(vec![]);
+    }
+    let mut parser = Parser { tokens: tokenize(trimmed), index: 0, licenses: vec![] };
+    let result = parser.or().and_then(|_| match parser.peek() {
+        None => Ok(()),
+        Some(token) => Err(parser.error(format!("unexpected '{}'", token))),
+    });
+    match result {
+        Ok(()) => Ok(parser.licenses),
+        // an expression without operators is probably a single misspelled identifier or license name
+        Err(error) => {
+            let has_operators = parser.tokens.iter().any(|token| matches!(token.as_str(), "AND" | "OR" | "WITH" | "(" | ")"));
+            match suggest_license(trimmed).filter(|_| !has_operators && parser.tokens.len() > 1) {
+                Some(suggestion) => Err(ExpressionError {
+                    message: format!("unknown SPDX license identifier '{}'", trimmed),
+                    suggestion: Some(suggestion.to_string()),
+                }),
+                None => Err(error),
+            }
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut token = String::new();
+    for c in expression.chars() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if !token.is_empty() {
+                tokens.push(std::mem::take(&mut token));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            token.push(c);
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    index: usize,
+    licenses: Vec<&'static License>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.index).map(|token| token.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn error(&self, message: String) -> ExpressionError {
+        ExpressionError { message, suggestion: None }
+    }
+
+    fn or(&mut self) -> Result<(), ExpressionError> {
+        self.and()?;
+        while self.peek() == Some("OR") {
+            self.next();
+            self.and()?;
+        }
+        Ok(())
+    }
+
+    fn and(&mut self) -> Result<(), ExpressionError> {
+        self.term()?;
+        while self.peek() == Some("AND") {
+            self.next();
+            self.term()?;
+        }
+        Ok(())
+    }
+
+    fn term(&mut self) -> Result<(), ExpressionError> {
+        let token = match self.next() {
+            Some(token) => token,
+            None => return Err(self.error("expression is incomplete".to_string())),
+        };
+        if token == "(" {
+            self.or()?;
+            return match self.next().as_deref() {
+                Some(")") => Ok(()),
+                _ => Err(self.error("missing ')'".to_string())),
+            };
+        }
+        if matches!(token.as_str(), ")" | "AND" | "OR" | "WITH") {
+            return Err(self.error(format!("expected a license identifier, found '{}'", token)));
+        }
+        self.license(&token)?;
+        if self.peek() == Some("WITH") {
+            self.next();
+            match self.next() {
+                Some(id) if exception(&id).is_some() => {}
+                Some(id) => {
+                    let suggestion = closest_match(&id, list().exceptions.iter().map(|exception| exception.id.as_str()));
+                    return Err(ExpressionError {
+                        message: format!("unknown SPDX license exception '{}'", id),
+                        suggestion: suggestion.map(String::from),
+                    });
+                }
+                None => return Err(self.error("missing license exception after 'WITH'".to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    fn license(&mut self, token: &str) -> Result<(), ExpressionError> {
+        if is_license_ref(token) {
+            return Ok(());
+        }
+        // deprecated identifiers like `GPL-2.0+` are on the list including the `+`
+        let id = match license(token) {
+            Some(_) => token,
+            None => token.strip_suffix('+').unwrap_or(token),
+        };
+        match license(id) {
+            Some(license) => {
+                self.licenses.push(license);
+                Ok(())
+            }
+            None => Err(ExpressionError {
+                message: format!("unknown SPDX license identifier '{}'", token),
+                suggestion: suggest_license(id).map(String::from),
+            }),
+        }
+    }
+}
+
+fn is_license_ref(token: &str) -> bool {
+    let is_id = |id: &str| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+    let reference = match token.split_once(':') {
+        Some((document, reference)) => match document.strip_prefix("DocumentRef-") {
+            Some(document) if is_id(document) => reference,
+            _ => return false,
+        },
+        None => token,
+    };
+    reference.strip_prefix("LicenseRef-").is_some_and(is_id)
+}
+
+/// Check the license identifiers (`/model_details/licenses/*/identifier`) of a Google model card.
+///
+/// Invalid expressions are errors, deprecated identifiers are warnings. Entries have the keyword `spdx`
+/// and carry the suggested identifier.
+pub fn check_licenses(modelcard: &Value) -> Vec<ValidationEntry> {
+    let licenses = match modelcard.pointer("/model_details/licenses").and_then(Value::as_array) {
+        Some(licenses) => licenses,
+        None => return vec![],
+    };
+    let mut entries = vec![];
+    for (index, item) in licenses.iter().enumerate() {
+        let identifier = match item.get("identifier").and_then(Value::as_str) {
+            Some(identifier) => identifier,
+            None => continue,
+        };
+        let pointer = format!("/model_details/licenses/{}/identifier", index);
+        match validate_expression(identifier) {
+            Ok(referenced) => {
+                for license in referenced.into_iter().filter(|license| license.deprecated) {
+                    let suggestion = replacement_of(license);
+                    entries.push(ValidationEntry {
+                        pointer: pointer.clone(),
+                        keyword: KEYWORD.to_string(),
+                        severity: Severity::Warning,
+                        message: match suggestion {
+                            Some(replacement) => format!("SPDX license identifier '{}' is deprecated, use '{}'", license.id, replacement),
+                            None => format!("SPDX license identifier '{}' is deprecated", license.id),
+                        },
+                        suggestion: suggestion.map(String::from),
+                        ..Default::default()
+                    });
+                }
+            }
+            Err(error) => entries.push(ValidationEntry {
+                pointer,
+                keyword: KEYWORD.to_string(),
+                message: format!("'{}' is not a valid SPDX license expression: {}", identifier, error),
+                suggestion: error.suggestion,
+                ..Default::default()
+            }),
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_license_list() {
+        // the complete list, not a selection of popular licenses
+        assert!(licenses().len() >= 700, "{}", licenses().len());
+        assert!(list().exceptions.len() >= 79, "{}", list().exceptions.len());
+        assert_eq!(license_list_version(), "3.27.0");
+        for id in ["curl", "JSON", "libpng-2.0", "bzip2-1.0.6", "Zlib", "Unicode-3.0"] {
+            assert!(license(id).is_some(), "{}", id);
+        }
+        assert!(validate_expression("libpng-2.0 AND bzip2-1.0.6").is_ok());
+        assert!(license("MIT").unwrap().osi_approved);
+        assert!(license("GPL-3.0").unwrap().deprecated);
+        assert_eq!(license_url("mit"), Some("https://spdx.org/licenses/MIT.html".to_string()));
+        assert_eq!(license_url("proprietary"), None);
+        assert!(exception("llvm-exception").is_some());
+    }
+
+    #[test]
+    fn test_valid_expressions() {
+        for expression in ["MIT", "mit", "GPL-2.0+", "MIT OR Apache-2.0", "(MIT OR Apache-2.0) AND BSD-3-Clause",
+            "Apache-2.0 WITH LLVM-exception", "LicenseRef-Internal", "DocumentRef-spdx-tool-1.2:LicenseRef-MIT-Style-2",
+            "NOASSERTION", "proprietary", "((MIT))"] {
+            assert!(validate_expression(expression).is_ok(), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        let cases = [
+            ("Apache 2.0", Some("Apache-2.0")),
+            ("Apache License 2.0", Some("Apache-2.0")),
+            ("MIT or Apache-2.0", None),
+            ("MIT AND", None),
+            ("(MIT OR Apache-2.0", None),
+            ("MIT OR Apcahe-2.0", Some("Apache-2.0")),
+            ("Apache-2.0 WITH LLVM-exeption", Some("LLVM-exception")),
+            ("LicenseRef-", None),
+            ("", None),
+        ];
+        for (expression, suggestion) in cases {
+            let error = validate_expression(expression).unwrap_err();
+            assert_eq!(error.suggestion.as_deref(), suggestion, "{}: {}", expression, error);
+        }
+        assert_eq!(validate_expression("Apache 2.0").unwrap_err().to_string(), "unknown SPDX license identifier 'Apache 2.0', did you mean 'Apache-2.0'?");
+    }
+
+    #[test]
+    fn test_check_licenses() {
+        let modelcard = json!({"model_details": {"licenses": [
+            {"identifier": "Apache-2.0"},
+            {"identifier": "Apache 2.0"},
+            {"identifier": "GPL-3.0+"},
+            {"custom_text": "Internal use only"}
+        ]}});
+        let entries = check_licenses(&modelcard);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].pointer, "/model_details/licenses/1/identifier");
+        assert_eq!(entries[0].severity, Severity::Error);
+        assert_eq!(entries[0].suggestion.as_deref(), Some("Apache-2.0"));
+        assert_eq!(entries[1].severity, Severity::Warning);
+        assert_eq!(entries[1].message, "SPDX license identifier 'GPL-3.0+' is deprecated, use 'GPL-3.0-or-later'");
+    }
+}
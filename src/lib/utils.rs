@@ -6,8 +6,15 @@
 //!
 //! - `strip_unc`: Removes the UNC prefix from a Windows path.
 //! - `create_file`: Creates a file with the specified content.
-//! - `load_json_file`: Loads and deserializes a JSON file into a [`serde_json::Value`].
+//! - `read_source`: Reads a source file, or stdin for `-`.
+//! - `write_target`: Writes content to a target file, or stdout for `-`.
+//! - `load_json_file`: Loads and deserializes a JSON, YAML, TOML or Markdown front matter file into a [`serde_json::Value`].
+//! - `load_data_file`: Loads and deserializes a data file in the given or detected [`DataFormat`].
+//! - `parse_data`: Deserializes a string in the given [`DataFormat`] into a [`serde_json::Value`].
+//! - `format_data`: Serializes a [`serde_json::Value`] in the given [`DataFormat`].
 //! - `is_directory_empty`: Check if a directory is empty.
+//! - `edit_distance`: Counts the character edits needed to turn one string into another.
+//! - `closest_match`: Finds the candidate closest to a misspelled word, used for "did you mean" suggestions.
 //!
 //! ## Notes
 //!
@@ -15,12 +22,15 @@
 //! - The `create_file` and `load_json_file` functions use [`anyhow::Result`] for error handling, allowing for simple and flexible error management.
 
 use std::{
+    fmt,
     fs::File,
-    io::{Read, Write},
-    path::Path
+    io::{self, Read, Write},
+    path::Path,
+    str::FromStr
 };
 
 use anyhow::{bail, Context, Result};
+use serde_json::Value;
 
 /// const will be used to remove the network part of the UNC to display users a more common path on windows systems.
 const LOCAL_UNC: &str = "\\\\?\\";
@@ -65,11 +75,11 @@ pub fn strip_unc(path: &Path) -> String {
 /// ## Example
 /// 
 /// ```rust
-/// use std::path::Path;
+/// use std::env::temp_dir;
 /// use modelcards::utils::create_file;
 ///
-/// let path = Path::new("your_path.txt");
-/// create_file(path, "File content").expect("Failed to create file");
+/// let path = temp_dir().join("modelcards_create_file.txt");
+/// create_file(&path, "File content").expect("Failed to create file");
 /// ```
 /// 
 pub fn create_file(path: &Path, content: &str) -> Result<()> {
@@ -78,8 +88,214 @@ pub fn create_file(path: &Path, content: &str) -> Result<()> {
     Ok(())
 }
 
+/// Path given as source or target to read from stdin or write to stdout.
+pub const STDIO: &str = "-";
+
+/// Reads the content of a source file, or of stdin if the source is [`STDIO`] (`-`).
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use modelcards::utils::read_source;
+/// 
+/// let piped = read_source("-").expect("Failed to read stdin");
+/// ```
+/// 
+pub fn read_source(source: &str) -> Result<String> {
+    let mut content = String::new();
+    if source == STDIO {
+        io::stdin().read_to_string(&mut content).context("Failed to read from stdin")?;
+    } else {
+        File::open(source)
+            .and_then(|mut file| file.read_to_string(&mut content))
+            .with_context(|| format!("Failed to read file {}", source))?;
+    }
+    Ok(content)
+}
+
+/// Writes content to a target file, or to stdout if the target is [`STDIO`] (`-`).
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use modelcards::utils::write_target;
+/// 
+/// write_target("-", "# Model Card").expect("Failed to write to stdout");
+/// ```
+/// 
+pub fn write_target(target: &str, content: &str) -> Result<()> {
+    if target == STDIO {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(content.as_bytes()).context("Failed to write to stdout")?;
+        if !content.ends_with('\n') {
+            stdout.write_all(b"\n")?;
+        }
+        return stdout.flush().context("Failed to write to stdout");
+    }
+    create_file(Path::new(target), content)
+}
+
+/// Format of a modelcard data file.
+/// 
+/// All formats are normalised into a [`serde_json::Value`], so YAML and TOML files can be merged, validated and rendered like JSON files.
+/// The format can be parsed from its name: `json`, `yaml` (or `yml`), `toml` and `markdown` (or `md`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DataFormat {
+    /// JSON data (default).
+    #[default]
+    Json,
+    /// YAML data.
+    Yaml,
+    /// TOML data.
+    Toml,
+    /// Markdown with YAML front matter, e.g. a Hugging Face `README.md`. Only the front matter is read.
+    Markdown,
+}
+
+impl DataFormat {
+    /// Detect the format of a file by its extension (`.json`, `.yaml`, `.yml`, `.toml` or `.md`).
+    /// 
+    /// ## Example
+    /// 
+    /// ```rust
+    /// use std::path::Path;
+    /// use modelcards::utils::DataFormat;
+    /// 
+    /// assert_eq!(DataFormat::from_path(Path::new("defaults.yaml")), Some(DataFormat::Yaml));
+    /// assert_eq!(DataFormat::from_path(Path::new("modelcard")), None);
+    /// ```
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(DataFormat::Json),
+            "yaml" | "yml" => Some(DataFormat::Yaml),
+            "toml" => Some(DataFormat::Toml),
+            "md" | "markdown" => Some(DataFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for DataFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(DataFormat::Json),
+            "yaml" | "yml" => Ok(DataFormat::Yaml),
+            "toml" => Ok(DataFormat::Toml),
+            "markdown" | "md" => Ok(DataFormat::Markdown),
+            other => bail!("Unknown data format '{}' (expected json, yaml, toml or markdown)", other),
+        }
+    }
+}
+
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataFormat::Json => write!(f, "json"),
+            DataFormat::Yaml => write!(f, "yaml"),
+            DataFormat::Toml => write!(f, "toml"),
+            DataFormat::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+/// Deserializes a string in the given format into a `serde_json::Value`.
+/// 
+/// TOML date and time values are converted to strings. Markdown is read from its YAML front matter,
+/// the block between the leading `---` lines.
+/// 
+/// ## Errors
+/// 
+/// The function will return an error if the content is not valid in the format or a Markdown document has no front matter.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::utils::{parse_data, DataFormat};
+/// 
+/// let value = parse_data("model_details:\n  name: My Model # the name\n", DataFormat::Yaml).unwrap();
+/// assert_eq!(value, json!({"model_details": {"name": "My Model"}}));
+/// ```
+/// 
+pub fn parse_data(content: &str, format: DataFormat) -> Result<Value> {
+    match format {
+        DataFormat::Json => Ok(serde_json::from_str(content)?),
+        DataFormat::Yaml => Ok(serde_yaml::from_str(content)?),
+        DataFormat::Toml => Ok(toml_to_json(toml::from_str(content)?)),
+        DataFormat::Markdown => match front_matter(content) {
+            Some(yaml) if yaml.trim().is_empty() => Ok(Value::Object(Default::default())),
+            Some(yaml) => Ok(serde_yaml::from_str(yaml)?),
+            None => bail!("Markdown has no YAML front matter (a block between '---' lines at the start)"),
+        },
+    }
+}
+
+/// Serializes a `serde_json::Value` in the given format, the counterpart of [`parse_data`].
+///
+/// JSON is pretty printed. Comments and formatting of the original file are not preserved.
+///
+/// ## Errors
+///
+/// The function will return an error if the value can not be represented in the format (e.g. `null` in TOML),
+/// or the format is Markdown, as the front matter can not be written without the document.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::utils::{format_data, DataFormat};
+///
+/// let yaml = format_data(&json!({"model_details": {"name": "My Model"}}), DataFormat::Yaml).unwrap();
+/// assert_eq!(yaml, "model_details:\n  name: My Model\n");
+/// ```
+///
+pub fn format_data(value: &Value, format: DataFormat) -> Result<String> {
+    match format {
+        DataFormat::Json => Ok(serde_json::to_string_pretty(value)? + "\n"),
+        DataFormat::Yaml => Ok(serde_yaml::to_string(value)?),
+        DataFormat::Toml => Ok(toml::to_string_pretty(value)?),
+        DataFormat::Markdown => bail!("Writing the front matter of Markdown documents is not supported"),
+    }
+}
+
+/// Extracts the YAML front matter of a Markdown document.
+fn front_matter(content: &str) -> Option<&str> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut lines = content.split_inclusive('\n');
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+    let start = content.find('\n')? + 1;
+    let mut end = start;
+    for line in lines {
+        if line.trim_end() == "---" {
+            return Some(&content[start..end]);
+        }
+        end += line.len();
+    }
+    None
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect()),
+    }
+}
+
 /// Load and deserializes a JSON file into a `serde_json::Value`.
 /// 
+/// The format is detected by the file extension (see [`DataFormat::from_path`]): YAML (`.yaml`, `.yml`), TOML (`.toml`)
+/// and the YAML front matter of Markdown files (`.md`, `.markdown`) are deserialized accordingly, `.json` and all
+/// other files are read as JSON.
+/// 
 /// ## Panics
 /// 
 /// This function uses `anyhow::Result` for error handling, allowing for simple and flexible error management.
@@ -96,10 +312,30 @@ pub fn create_file(path: &Path, content: &str) -> Result<()> {
 /// ```
 ///
 pub fn load_json_file(file_path: &Path) -> Result<serde_json::Value> {
+    load_data_file(file_path, None)
+}
+
+/// Load and deserializes a data file into a `serde_json::Value`.
+/// 
+/// If no format is given, the format is detected by the file extension (see [`DataFormat::from_path`]), falling back to JSON.
+/// 
+/// ## Example
+///
+/// ```rust,no_run
+/// use std::path::Path;
+/// use modelcards::utils::{load_data_file, DataFormat};
+///
+/// let defaults = load_data_file(Path::new("defaults.yaml"), None).expect("Failed to load YAML");
+/// let model = load_data_file(Path::new("model.card"), Some(DataFormat::Toml)).expect("Failed to load TOML");
+/// ```
+///
+pub fn load_data_file(file_path: &Path, format: Option<DataFormat>) -> Result<serde_json::Value> {
     let mut file = File::open(file_path).with_context(|| format!("Failed to open file {}", file_path.display()))?;
-        let mut file_string = String::new();
-        file.read_to_string(&mut file_string)?;
-        Ok(serde_json::from_str(&file_string)?)
+    let mut file_string = String::new();
+    file.read_to_string(&mut file_string)?;
+    let format = format.or_else(|| DataFormat::from_path(file_path)).unwrap_or_default();
+    parse_data(&file_string, format)
+        .with_context(|| format!("Invalid {} in file {}", format, file_path.display()))
 }
 
 /// Check if a directory is empty.
@@ -147,6 +383,69 @@ pub fn is_directory_empty(path: &Path, allow_hidden: bool) -> Result<bool> {
     Ok(false)
 }
 
+/// Counts the character edits needed to turn one string into another.
+/// 
+/// Computes the Damerau-Levenshtein distance (optimal string alignment), where inserting, deleting or replacing
+/// a character and swapping two adjacent characters each count as one edit.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use modelcards::utils::edit_distance;
+/// 
+/// assert_eq!(edit_distance("quantitive", "quantitative"), 2);
+/// assert_eq!(edit_distance("lisence", "license"), 2);
+/// assert_eq!(edit_distance("lciense", "license"), 1);
+/// ```
+/// 
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Finds the candidate closest to a misspelled word.
+/// 
+/// Candidates are compared case-insensitively. A candidate only matches if it needs at most a third of the
+/// characters of the word to be edited (at least one edit), so unrelated words yield `None`.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use modelcards::utils::closest_match;
+/// 
+/// let fields = ["model_details", "quantitative_analysis", "considerations"];
+/// assert_eq!(closest_match("quantitive_analysis", fields), Some("quantitative_analysis"));
+/// assert_eq!(closest_match("metrics", fields), None);
+/// ```
+/// 
+pub fn closest_match<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    let limit = (word.chars().count() / 3).max(1);
+    candidates.into_iter()
+        .map(|candidate| (edit_distance(&word, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
 /// Console utilities
 /// 
 /// Provides utility functions for logging messages to the console.
@@ -159,6 +458,7 @@ pub fn is_directory_empty(path: &Path, allow_hidden: bool) -> Result<bool> {
 /// - `error`: Logs an error message to the console.
 /// - `error_exit`: Logs an error message to the console and exits the program with an error code.
 /// - `warn`: Logs a warning message to the console.
+/// - `warning_exit`: Logs a warning message to the console and exits the program with the warning code.
 /// - `info`: Logs an information message to the console.
 /// - `success_exit`: Logs a success message to the console and exits the program with a success code.
 /// - `debug`: Logs a debug message to the console. Only available in debug builds.
@@ -167,7 +467,9 @@ pub fn is_directory_empty(path: &Path, allow_hidden: bool) -> Result<bool> {
 /// 
 /// - The `debug` function is only available in debug builds and will not be compiled in release builds.
 /// - All functions use the env_logger crate for logging messages to the console. Therefore visibility of messages can be controlled by setting the `RUST_LOG` environment variable.
-/// - The `error_exit` and `success_exit` functions will exit the program with an appropriate exit code (1 for error, 0 for success).
+/// - The `error_exit`, `warning_exit` and `success_exit` functions will exit the program with an appropriate exit code
+///   (1 for errors, 3 for warnings only, 0 for success). The warning code is used by validate, check, lint and build
+///   with `--deny-warnings` or `--strict`, so CI can tell warnings from errors.
 /// 
 pub mod console {
     //use std::io::Write;
@@ -194,9 +496,21 @@ pub mod console {
         }
     }
 
+    /// Exit code of a run which failed with errors.
+    pub const ERROR_EXIT_CODE: i32 = 1;
+    /// Exit code of a run which found warnings, but no errors.
+    pub const WARNING_EXIT_CODE: i32 = 3;
+
+    /// Logs an error message and exits with [`ERROR_EXIT_CODE`].
     pub fn error_exit(msg: &str, e: Option<impl std::fmt::Debug>) {
         error(msg, e);
-        std::process::exit(1);
+        std::process::exit(ERROR_EXIT_CODE);
+    }
+
+    /// Logs a warning message and exits with [`WARNING_EXIT_CODE`], so CI can tell warnings from errors.
+    pub fn warning_exit(msg: &str) {
+        warn(msg);
+        std::process::exit(WARNING_EXIT_CODE);
     }
 
     /// Logs a warning message to the console.
@@ -233,6 +547,7 @@ pub mod console {
         //println!("{}", msg);
     }
 
+    /// Logs an information message and exits with code 0.
     pub fn success_exit(msg: &str) {
         info(msg);
         std::process::exit(0);
@@ -327,6 +642,51 @@ mod tests {
         remove_dir_all(&dir).expect("Failed to remove test directory");
     }
 
+    #[test]
+    fn load_data_file_detects_format() {
+        let dir = get_temp_dir("test_load_data_file_detects_format", true);
+        let expected = serde_json::json!({"model_details": {"name": "Model", "version": {"date": "2024-01-01"}}, "tags": ["a", "b"]});
+        create_file(&dir.join("card.json"), r#"{"model_details": {"name": "Model", "version": {"date": "2024-01-01"}}, "tags": ["a", "b"]}"#).expect("Could not create file");
+        create_file(&dir.join("card.yml"), "# defaults\nmodel_details:\n  name: Model\n  version:\n    date: '2024-01-01'\ntags: [a, b]\n").expect("Could not create file");
+        create_file(&dir.join("card.toml"), "tags = [\"a\", \"b\"]\n[model_details]\nname = \"Model\"\n[model_details.version]\ndate = 2024-01-01\n").expect("Could not create file");
+        for file in ["card.json", "card.yml", "card.toml"] {
+            assert_eq!(load_json_file(&dir.join(file)).expect("Could not load file"), expected, "{}", file);
+        }
+        remove_dir_all(&dir).expect("Failed to remove test directory");
+    }
+
+    #[test]
+    fn load_data_file_with_explicit_format() {
+        let dir = get_temp_dir("test_load_data_file_with_explicit_format", true);
+        create_file(&dir.join("card.data"), "name: Model\n").expect("Could not create file");
+        assert!(load_json_file(&dir.join("card.data")).is_err());
+        let value = load_data_file(&dir.join("card.data"), Some(DataFormat::Yaml)).expect("Could not load file");
+        assert_eq!(value, serde_json::json!({"name": "Model"}));
+        remove_dir_all(&dir).expect("Failed to remove test directory");
+    }
+
+    #[test]
+    fn parse_markdown_front_matter() {
+        let readme = "---\nlicense: mit\ntags:\n- audio\n---\n\n# My Model\n\n---\n";
+        assert_eq!(parse_data(readme, DataFormat::Markdown).unwrap(), serde_json::json!({"license": "mit", "tags": ["audio"]}));
+        assert_eq!(parse_data("---\r\n---\r\n# Model", DataFormat::Markdown).unwrap(), serde_json::json!({}));
+        assert!(parse_data("# My Model\n---\nlicense: mit\n---\n", DataFormat::Markdown).is_err());
+        assert!(parse_data("---\nlicense: mit\n", DataFormat::Markdown).is_err());
+        assert_eq!(DataFormat::from_path(Path::new("README.md")), Some(DataFormat::Markdown));
+        assert_eq!("md".parse::<DataFormat>().unwrap().to_string(), "markdown");
+    }
+
+    #[test]
+    fn format_data_round_trip() {
+        let value = serde_json::json!({"schema_version": "2.0", "model_details": {"name": "Model", "licenses": [{"identifier": "MIT"}]}});
+        for format in [DataFormat::Json, DataFormat::Yaml, DataFormat::Toml] {
+            let content = format_data(&value, format).unwrap();
+            assert_eq!(parse_data(&content, format).unwrap(), value, "{}", format);
+        }
+        assert!(format_data(&serde_json::json!({"name": null}), DataFormat::Toml).is_err());
+        assert!(format_data(&value, DataFormat::Markdown).is_err());
+    }
+
     #[test]
     fn create_file_test() {
         let dir = get_temp_dir("test_create_file", true);
@@ -0,0 +1,275 @@
+//! Cross-field consistency checks of Google model cards.
+//!
+//! JSON Schema validates every value on its own, but the sections of a model card refer to each other:
+//!
+//! - Datasets (`/model_parameters/data/*`) are referred to by their `name`, which must be unique.
+//! - The `slice` of a performance metric names a dataset or the features it is sliced by, e.g. `Race: Black`
+//!   is a slice by the `race` feature, which must be a sensitive field or a graphic feature of a dataset.
+//! - The graphics of the quantitative analysis are named `<metric> | <features>`, e.g.
+//!   `binary_accuracy | Race, Sex`, where the metric must be the type of a performance metric.
+//! - The `date` of the model version must be a valid date (`YYYY-MM-DD`), which is not in the future.
+//!
+//! Dangling references and invalid dates are errors, references to unknown features and duplicate graphic
+//! names are warnings. Entries have the keyword `consistency`.
+
+use std::{collections::BTreeSet, time::{SystemTime, UNIX_EPOCH}};
+use serde_json::Value;
+
+use crate::utils::closest_match;
+use super::{Severity, ValidationEntry};
+
+/// Keyword of the validation entries.
+const KEYWORD: &str = "consistency";
+
+/// Check that the datasets, metric slices, graphics and version date of a Google model card fit together.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::validate::{check_consistency, Severity};
+///
+/// let modelcard = json!({
+///     "model_parameters": {"data": [{"name": "Eval Set", "sensitive": {"sensitive_data": ["race"]}}]},
+///     "quantitative_analysis": {
+///         "performance_metrics": [{"type": "accuracy", "value": "0.9", "slice": "Race: Black"}],
+///         "graphics": {"collection": [{"name": "acuracy | Race"}]}
+///     }
+/// });
+/// let entries = check_consistency(&modelcard);
+/// assert_eq!(entries.len(), 1);
+/// assert_eq!(entries[0].pointer, "/quantitative_analysis/graphics/collection/0/name");
+/// assert_eq!(entries[0].severity, Severity::Error);
+/// assert_eq!(entries[0].suggestion.as_deref(), Some("accuracy"));
+/// ```
+pub fn check_consistency(modelcard: &Value) -> Vec<ValidationEntry> {
+    let mut entries = vec![];
+    let datasets = items(modelcard, "/model_parameters/data");
+    let metrics = items(modelcard, "/quantitative_analysis/performance_metrics");
+
+    // dataset names identify the datasets
+    let mut names: Vec<(usize, &str)> = vec![];
+    for (index, dataset) in datasets.iter().enumerate() {
+        let name = match dataset.get("name").and_then(Value::as_str) {
+            Some(name) => name,
+            None => continue,
+        };
+        match names.iter().find(|(_, other)| other.trim().eq_ignore_ascii_case(name.trim())) {
+            Some((first, _)) => entries.push(entry(
+                format!("/model_parameters/data/{}/name", index),
+                Severity::Error,
+                format!("dataset name '{}' is already used by /model_parameters/data/{}", name, first),
+                None,
+            )),
+            None => names.push((index, name)),
+        }
+    }
+
+    // features the datasets can be sliced by: dataset names, sensitive fields and the features of dataset graphics
+    let mut features: BTreeSet<String> = names.iter().map(|(_, name)| normalize(name)).collect();
+    for dataset in &datasets {
+        for field in items(dataset, "/sensitive/sensitive_data") {
+            if let Some(field) = field.as_str() {
+                features.insert(normalize(field));
+            }
+        }
+        for graphic in items(dataset, "/graphics/collection") {
+            if let Some((_, sliced_by)) = graphic.get("name").and_then(Value::as_str).and_then(|name| name.split_once('|')) {
+                features.extend(split_features(sliced_by));
+            }
+        }
+    }
+
+    // metric slices refer to datasets or their features
+    for (index, metric) in metrics.iter().enumerate() {
+        let slice = match metric.get("slice").and_then(Value::as_str) {
+            Some(slice) if !slice.trim().is_empty() && !features.is_empty() => slice,
+            _ => continue,
+        };
+        if features.contains(&normalize(slice)) {
+            continue;
+        }
+        let sliced_by = slice.split_once(':').map_or(slice, |(sliced_by, _)| sliced_by);
+        for feature in split_features(sliced_by).into_iter().filter(|feature| !features.contains(feature)) {
+            entries.push(entry(
+                format!("/quantitative_analysis/performance_metrics/{}/slice", index),
+                Severity::Warning,
+                format!("slice '{}' refers to '{}', which is neither a dataset nor a feature of a dataset", slice, feature),
+                closest_match(&feature, features.iter().map(String::as_str)).map(String::from),
+            ));
+        }
+    }
+
+    // graphics of the quantitative analysis are named after the metric and the features they show
+    let types: BTreeSet<&str> = metrics.iter().filter_map(|metric| metric.get("type").and_then(Value::as_str)).collect();
+    let mut graphics: Vec<(usize, &str)> = vec![];
+    for (index, graphic) in items(modelcard, "/quantitative_analysis/graphics/collection").iter().enumerate() {
+        let name = match graphic.get("name").and_then(Value::as_str) {
+            Some(name) => name,
+            None => continue,
+        };
+        let pointer = format!("/quantitative_analysis/graphics/collection/{}/name", index);
+        if let Some((first, _)) = graphics.iter().find(|(_, other)| *other == name) {
+            entries.push(entry(pointer.clone(), Severity::Warning, format!("graphic name '{}' is already used by /quantitative_analysis/graphics/collection/{}", name, first), None));
+        } else {
+            graphics.push((index, name));
+        }
+        let (metric, sliced_by) = match name.split_once('|') {
+            Some((metric, sliced_by)) => (metric.trim(), sliced_by),
+            None => continue,
+        };
+        if !types.is_empty() && !types.iter().any(|kind| kind.eq_ignore_ascii_case(metric)) {
+            entries.push(entry(
+                pointer.clone(),
+                Severity::Error,
+                format!("graphic '{}' refers to metric '{}', which is not the type of a performance metric", name, metric),
+                closest_match(metric, types.iter().copied()).map(String::from),
+            ));
+        }
+        if features.is_empty() {
+            continue;
+        }
+        for feature in split_features(sliced_by).into_iter().filter(|feature| !features.contains(feature)) {
+            entries.push(entry(
+                pointer.clone(),
+                Severity::Warning,
+                format!("graphic '{}' refers to '{}', which is neither a dataset nor a feature of a dataset", name, feature),
+                closest_match(&feature, features.iter().map(String::as_str)).map(String::from),
+            ));
+        }
+    }
+
+    // the version date is a date of the past
+    if let Some(date) = modelcard.pointer("/model_details/version/date").and_then(Value::as_str) {
+        let pointer = "/model_details/version/date".to_string();
+        match parse_date(date) {
+            None => entries.push(entry(pointer, Severity::Error, format!("version date '{}' is not a valid date (YYYY-MM-DD)", date), None)),
+            Some(parsed) if parsed > today() => entries.push(entry(pointer, Severity::Warning, format!("version date '{}' is in the future", date), None)),
+            Some(_) => {}
+        }
+    }
+    entries
+}
+
+fn entry(pointer: String, severity: Severity, message: String, suggestion: Option<String>) -> ValidationEntry {
+    ValidationEntry {
+        pointer,
+        keyword: KEYWORD.to_string(),
+        severity,
+        message: match &suggestion {
+            Some(suggestion) => format!("{}, did you mean '{}'?", message, suggestion),
+            None => message,
+        },
+        suggestion,
+        ..Default::default()
+    }
+}
+
+/// The items of the array at a JSON pointer, empty if there is none.
+fn items<'a>(value: &'a Value, pointer: &str) -> Vec<&'a Value> {
+    value.pointer(pointer).and_then(Value::as_array).map(|items| items.iter().collect()).unwrap_or_default()
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Split a list of features like `Race, Sex` or `race/sex`.
+fn split_features(features: &str) -> Vec<String> {
+    features.split([',', '/', '&']).map(normalize).filter(|feature| !feature.is_empty()).collect()
+}
+
+/// Parse a `YYYY-MM-DD` date into `(year, month, day)`.
+fn parse_date(date: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = date.trim().splitn(3, '-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let (year, month, day): (u32, u32, u32) = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days).contains(&day).then_some((year, month, day))
+}
+
+/// The current date (UTC) as `(year, month, day)`.
+fn today() -> (u32, u32, u32) {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs() / 86_400).unwrap_or_default() as i64;
+    // civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year as u32, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::assets;
+
+    #[test]
+    fn test_consistency_of_google_sample() {
+        let sample: Value = serde_json::from_str(assets::schema::get_sample()).unwrap();
+        assert_eq!(check_consistency(&sample), vec![]);
+    }
+
+    #[test]
+    fn test_dangling_references() {
+        let modelcard = json!({
+            "model_details": {"version": {"name": "1.0", "date": "2023-02-30"}},
+            "model_parameters": {"data": [
+                {"name": "Train Set", "sensitive": {"sensitive_data": ["race", "sex"]}},
+                {"name": "train set", "graphics": {"collection": [{"name": "counts | Age"}]}}
+            ]},
+            "quantitative_analysis": {
+                "performance_metrics": [
+                    {"type": "accuracy", "value": "0.9", "slice": "Train Set"},
+                    {"type": "accuracy", "value": "0.8", "slice": "Race: Black"},
+                    {"type": "recall", "value": "0.7", "slice": "Rase, Age: Black, 20-30"}
+                ],
+                "graphics": {"collection": [
+                    {"name": "recall | Race"},
+                    {"name": "precision | Sex, Income"},
+                    {"name": "recall | Race"},
+                    {"name": "ROC curve"}
+                ]}
+            }
+        });
+        let entries: Vec<String> = check_consistency(&modelcard).iter()
+            .map(|entry| format!("{:?}", (entry.pointer.as_str(), entry.severity, entry.suggestion.as_deref())))
+            .collect();
+        assert_eq!(entries, vec![
+            r#"("/model_parameters/data/1/name", Error, None)"#,
+            r#"("/quantitative_analysis/performance_metrics/2/slice", Warning, Some("race"))"#,
+            r#"("/quantitative_analysis/graphics/collection/1/name", Error, None)"#,
+            r#"("/quantitative_analysis/graphics/collection/1/name", Warning, None)"#,
+            r#"("/quantitative_analysis/graphics/collection/2/name", Warning, None)"#,
+            r#"("/model_details/version/date", Error, None)"#,
+        ]);
+    }
+
+    #[test]
+    fn test_version_dates() {
+        assert_eq!(parse_date("2024-02-29"), Some((2024, 2, 29)));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("24-01-01"), None);
+        assert!(today() > (2024, 1, 1));
+        let entries = check_consistency(&json!({"model_details": {"version": {"date": "9999-12-31"}}}));
+        assert_eq!(entries[0].severity, Severity::Warning);
+        assert!(check_consistency(&json!({"model_details": {"version": {"date": "2020-07-28"}}})).is_empty());
+    }
+}
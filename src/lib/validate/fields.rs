@@ -0,0 +1,155 @@
+//! Warnings for fields a schema allows, but does not recommend.
+//!
+//! JSON Schema accepts fields marked as `deprecated` and, unless `additionalProperties` is `false`, fields the
+//! schema does not declare at all. Both make a model card valid, but usually point to an outdated card or a
+//! misspelled optional field (`licence` instead of `licenses`), so they are reported as warnings:
+//!
+//! - `deprecated` - The value of a property with `"deprecated": true`.
+//! - `unknown-field` - A field of an object whose schema declares `properties`, but not this one.
+//!
+//! Objects whose schema sets `additionalProperties` (or `patternProperties`, `unevaluatedProperties`) or combines
+//! subschemas with `allOf`, `anyOf` or `oneOf` are not checked for unknown fields, as the schema decides about
+//! them itself. Only local references (`#/definitions/...`) of the schema are followed.
+
+use serde_json::Value;
+
+use crate::{merge::escape_pointer_token, utils::closest_match};
+use super::{score::resolve, Severity, ValidationEntry};
+
+/// Maximum nesting of values followed, guards against recursive schemas.
+const MAX_DEPTH: usize = 16;
+/// Keywords of a schema which decide about undeclared fields.
+const OPEN_KEYWORDS: [&str; 6] = ["additionalProperties", "patternProperties", "unevaluatedProperties", "allOf", "anyOf", "oneOf"];
+
+/// Check a model card for deprecated fields and fields the schema does not declare.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::validate::{check_fields, Severity};
+///
+/// let schema = json!({"properties": {
+///     "licenses": {"type": "array"},
+///     "license": {"type": "string", "deprecated": true}
+/// }});
+/// let entries = check_fields(&json!({"license": "MIT", "licences": []}), &schema);
+/// assert_eq!(entries.len(), 2);
+/// assert_eq!(entries[0].keyword, "unknown-field");
+/// assert_eq!(entries[0].suggestion.as_deref(), Some("licenses"));
+/// assert_eq!(entries[1].keyword, "deprecated");
+/// assert!(entries.iter().all(|entry| entry.severity == Severity::Warning));
+/// ```
+pub fn check_fields(modelcard: &Value, schema: &Value) -> Vec<ValidationEntry> {
+    let mut entries = vec![];
+    walk(schema, schema, modelcard, "", 0, &mut entries);
+    entries
+}
+
+fn walk(root: &Value, node: &Value, value: &Value, pointer: &str, depth: usize, entries: &mut Vec<ValidationEntry>) {
+    let node = resolve(root, node);
+    if depth > MAX_DEPTH {
+        return;
+    }
+    match value {
+        Value::Object(fields) => {
+            let Some(properties) = node.get("properties").and_then(Value::as_object) else {
+                return;
+            };
+            let closed = OPEN_KEYWORDS.iter().all(|keyword| node.get(keyword).is_none());
+            for (key, child) in fields {
+                let child_pointer = format!("{}/{}", pointer, escape_pointer_token(key));
+                match properties.get(key).map(|property| resolve(root, property)) {
+                    Some(property) => {
+                        if property.get("deprecated").and_then(Value::as_bool) == Some(true) {
+                            let message = match property.get("description").and_then(Value::as_str) {
+                                Some(description) => format!("field '{}' is deprecated: {}", key, description),
+                                None => format!("field '{}' is deprecated", key),
+                            };
+                            entries.push(entry(child_pointer.clone(), "deprecated", message, None));
+                        }
+                        walk(root, property, child, &child_pointer, depth + 1, entries);
+                    }
+                    None if closed => {
+                        let suggestion = closest_match(key, properties.keys().map(String::as_str)).map(String::from);
+                        let message = format!("field '{}' is not declared by the schema", key);
+                        entries.push(entry(child_pointer, "unknown-field", message, suggestion));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(schema) = node.get("items").filter(|schema| schema.is_object()) {
+                for (index, item) in items.iter().enumerate() {
+                    walk(root, schema, item, &format!("{}/{}", pointer, index), depth + 1, entries);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn entry(pointer: String, keyword: &str, message: String, suggestion: Option<String>) -> ValidationEntry {
+    ValidationEntry {
+        pointer,
+        keyword: keyword.to_string(),
+        severity: Severity::Warning,
+        message: match &suggestion {
+            Some(suggestion) => format!("{}, did you mean '{}'?", message, suggestion),
+            None => message,
+        },
+        suggestion,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::assets;
+
+    #[test]
+    fn test_fields_of_google_sample() {
+        let schema: Value = serde_json::from_str(assets::schema::get_schema()).unwrap();
+        let sample: Value = serde_json::from_str(assets::schema::get_sample()).unwrap();
+        assert_eq!(check_fields(&sample, &schema), vec![]);
+    }
+
+    #[test]
+    fn test_unknown_nested_fields() {
+        let schema: Value = serde_json::from_str(assets::schema::get_schema()).unwrap();
+        let modelcard = json!({
+            "model_details": {"name": "Model", "overveiw": "A model", "owners": [{"name": "Jane", "email": "jane@example.org"}]},
+            "model_parameters": {"data": [{"name": "Train", "graphics": {"collection": [{"name": "counts", "url": "counts.png"}]}}]},
+            "custom": {"team": "vision"}
+        });
+        let entries = check_fields(&modelcard, &schema);
+        let entries: Vec<(&str, &str, Option<&str>)> = entries.iter()
+            .map(|entry| (entry.pointer.as_str(), entry.keyword.as_str(), entry.suggestion.as_deref()))
+            .collect();
+        assert_eq!(entries, vec![
+            ("/model_details/overveiw", "unknown-field", Some("overview")),
+            ("/model_details/owners/0/email", "unknown-field", None),
+            ("/model_parameters/data/0/graphics/collection/0/url", "unknown-field", None),
+        ]);
+    }
+
+    #[test]
+    fn test_open_schemas() {
+        let schema = json!({
+            "properties": {
+                "tags": {"type": "object", "properties": {"name": {}}, "additionalProperties": true},
+                "metrics": {"type": "array", "items": {"$ref": "#/definitions/metric"}}
+            },
+            "definitions": {"metric": {"properties": {"type": {}, "value": {}, "slice": {"deprecated": true, "description": "Use slices"}}}}
+        });
+        let entries = check_fields(&json!({"tags": {"name": "a", "team": "b"}, "metrics": [{"type": "a", "slice": "b"}]}), &schema);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].pointer, "/metrics/0/slice");
+        assert_eq!(entries[0].message, "field 'slice' is deprecated: Use slices");
+        // a schema without properties allows every field
+        assert!(check_fields(&json!({"name": "Model"}), &json!({"type": "object"})).is_empty());
+    }
+}
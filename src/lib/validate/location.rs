@@ -0,0 +1,506 @@
+//! Source locations of validation entries.
+//!
+//! Validation runs on the merged modelcard, so the JSON pointer of an entry does not tell which layer to fix.
+//! Using the merge [`Provenance`], every entry is attributed to the source file that set the invalid value,
+//! and for JSON and YAML files (including the front matter of Markdown files) a position-aware scan of the file
+//! yields the line and column of the value. Entries in TOML files are located by file only.
+
+use std::{collections::{BTreeSet, HashMap}, fmt, fs, path::Path};
+use serde::Serialize;
+
+use crate::{
+    merge::{escape_pointer_token, Origin, Provenance},
+    utils::{DataFormat, STDIO}
+};
+use super::{ValidationEntry, ValidationReport};
+
+/// Number of lines shown before and after the located line in a code frame.
+const FRAME_CONTEXT: usize = 1;
+
+/// Location of a value in a source file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceLocation {
+    /// The source file which set the value.
+    pub file: String,
+    /// Line of the value in the file (1-based), if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Column of the value in the line (1-based), if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{}:{}:{}", self.file, line, column),
+            (Some(line), None) => write!(f, "{}:{}", self.file, line),
+            _ => write!(f, "{}", self.file),
+        }
+    }
+}
+
+impl SourceLocation {
+    /// Render the located line with its surrounding lines and a marker below the column.
+    ///
+    /// Returns `None` if the location has no line or the file can not be read.
+    pub fn code_frame(&self) -> Option<String> {
+        let line = self.line?;
+        let content = fs::read_to_string(&self.file).ok()?;
+        let lines: Vec<&str> = content.lines().collect();
+        if line == 0 || line > lines.len() {
+            return None;
+        }
+        let first = line.saturating_sub(FRAME_CONTEXT).max(1);
+        let last = (line + FRAME_CONTEXT).min(lines.len());
+        let width = last.to_string().len();
+        let mut frame = format!("{:width$} |\n", "", width = width);
+        for number in first..=last {
+            frame.push_str(&format!("{:>width$} | {}\n", number, lines[number - 1], width = width));
+            if number == line {
+                let column = self.column.unwrap_or(1);
+                frame.push_str(&format!("{:width$} | {:column$}^\n", "", "", width = width, column = column - 1));
+            }
+        }
+        Some(frame)
+    }
+}
+
+impl ValidationReport {
+    /// Attribute all entries to the source files which set the invalid values.
+    ///
+    /// The source file of an entry is the file that set the value at its JSON pointer (or the first value
+    /// below it). Entries of the whole modelcard (e.g. a missing required field) are only attributed to a file
+    /// if all values were set by that file. For JSON and YAML files the line and column of the value are
+    /// determined as well.
+    pub fn locate(&mut self, provenance: &Provenance) {
+        let mut positions: HashMap<String, HashMap<String, (usize, usize)>> = HashMap::new();
+        for entry in self.entries.iter_mut() {
+            locate_entry(entry, provenance, &mut positions);
+        }
+    }
+}
+
+fn locate_entry(entry: &mut ValidationEntry, provenance: &Provenance, positions: &mut HashMap<String, HashMap<String, (usize, usize)>>) {
+    for sub_error in entry.sub_errors.iter_mut() {
+        locate_entry(sub_error, provenance, positions);
+    }
+    let prefix = format!("{}/", entry.pointer);
+    let (file, source_pointer) = if entry.pointer.is_empty() {
+        // the first value of the modelcard would blame an arbitrary layer
+        let sources: BTreeSet<&str> = provenance.values().map(|origin| origin.source.as_str()).collect();
+        match sources.into_iter().collect::<Vec<_>>().as_slice() {
+            [source] => (source.to_string(), String::new()),
+            _ => return,
+        }
+    } else {
+        let origin = provenance.get_key_value(&entry.pointer)
+            .or_else(|| provenance.iter().find(|(pointer, _)| pointer.starts_with(&prefix)));
+        match origin {
+            Some((pointer, origin)) => (origin.source.clone(), source_pointer(&entry.pointer, pointer, origin)),
+            None => return,
+        }
+    };
+    let positions = positions.entry(file.clone()).or_insert_with(|| {
+        match fs::read_to_string(&file) {
+            Ok(content) if file != STDIO => match DataFormat::from_path(Path::new(&file)) {
+                Some(DataFormat::Yaml | DataFormat::Markdown) => yaml_positions(&content),
+                Some(DataFormat::Toml) => HashMap::new(),
+                _ => json_positions(&content),
+            },
+            _ => HashMap::new(),
+        }
+    });
+    // values missing in the file (e.g. set by a patch) are located at their closest parent
+    let mut pointer = source_pointer.as_str();
+    let position = loop {
+        if let Some(position) = positions.get(pointer) {
+            break Some(*position);
+        }
+        match pointer.rfind('/') {
+            Some(index) => pointer = &pointer[..index],
+            None => break None,
+        }
+    };
+    entry.location = Some(SourceLocation {
+        file,
+        line: position.map(|(line, _)| line),
+        column: position.map(|(_, column)| column),
+    });
+}
+
+/// The pointer in its source file of the value at `pointer`, given the origin of the leaf at `leaf` at or below it.
+///
+/// Array items moved by the merge have another index in the source file, the tokens below `pointer` are dropped
+/// from the source pointer of the leaf.
+fn source_pointer(pointer: &str, leaf: &str, origin: &Origin) -> String {
+    let Some(source) = &origin.source_pointer else {
+        return pointer.to_string();
+    };
+    let depth = leaf[pointer.len()..].matches('/').count();
+    let mut source = source.as_str();
+    for _ in 0..depth {
+        source = source.rfind('/').map_or("", |index| &source[..index]);
+    }
+    source.to_string()
+}
+
+/// Determine the line and column (1-based) of every value in a JSON text, keyed by JSON pointer.
+///
+/// Members of objects are located at their key. Texts that are not JSON (e.g. YAML) yield no or partial positions.
+pub fn json_positions(text: &str) -> HashMap<String, (usize, usize)> {
+    let mut scanner = Scanner { chars: text.chars().collect(), index: 0, line: 1, column: 1, positions: HashMap::new() };
+    scanner.skip_whitespace();
+    let position = (scanner.line, scanner.column);
+    if matches!(scanner.peek(), Some('{') | Some('[')) {
+        scanner.positions.insert(String::new(), position);
+        scanner.value("");
+    }
+    scanner.positions
+}
+
+/// Determine the line and column (1-based) of every value in a YAML text, keyed by JSON pointer.
+///
+/// Members of mappings are located at their key, items of sequences at their `-`. Only block style is
+/// scanned: values of flow collections (`[a, b]`, `{a: 1}`) and multi-line scalars are located at their key.
+/// A Markdown file is scanned up to the end of its front matter.
+pub fn yaml_positions(text: &str) -> HashMap<String, (usize, usize)> {
+    let mut positions = HashMap::new();
+    // open collections: indentation of their entries, JSON pointer and the next index of a sequence
+    let mut stack: Vec<(usize, String, Option<usize>)> = vec![];
+    // the entry whose value starts on a following line
+    let mut pending = Some(String::new());
+    // indentation of the key of a block scalar (`|` or `>`) whose lines are skipped
+    let mut block: Option<usize> = None;
+    for (number, line) in text.lines().enumerate() {
+        let content = line.trim_start_matches(' ');
+        let mut indent = line.len() - content.len();
+        if content.trim().is_empty() || content.starts_with('#') {
+            continue;
+        }
+        if content.trim_end() == "---" || content.trim_end() == "..." {
+            if positions.is_empty() {
+                continue;
+            }
+            // the end of the front matter or the start of the next document
+            break;
+        }
+        match block {
+            Some(key_indent) if indent > key_indent => continue,
+            _ => block = None,
+        }
+        if positions.is_empty() {
+            positions.insert(String::new(), (number + 1, indent + 1));
+        }
+        let mut rest = content;
+        loop {
+            let item = rest == "-" || rest.starts_with("- ");
+            while stack.last().is_some_and(|(entry_indent, _, index)| {
+                *entry_indent > indent || (*entry_indent == indent && index.is_some() && !item)
+            }) {
+                stack.pop();
+            }
+            let opens = match stack.last() {
+                Some((entry_indent, _, index)) => *entry_indent < indent || (item && index.is_none()),
+                None => true,
+            };
+            if opens {
+                match pending.take() {
+                    Some(pointer) => stack.push((indent, pointer, if item { Some(0) } else { None })),
+                    None => break,
+                }
+            }
+            let Some((_, parent, index)) = stack.last_mut() else { break };
+            if item {
+                let Some(next) = index.as_mut() else { break };
+                let child = format!("{}/{}", parent, next);
+                *next += 1;
+                positions.insert(child.clone(), (number + 1, indent + 1));
+                let value = rest[1..].trim_start_matches(' ');
+                indent += rest.len() - value.len();
+                rest = value;
+                pending = Some(child);
+                if rest.is_empty() || rest.starts_with('#') {
+                    break;
+                }
+                continue;
+            }
+            let Some((key, value)) = yaml_key(rest) else { break };
+            let child = format!("{}/{}", parent, escape_pointer_token(&key));
+            positions.insert(child.clone(), (number + 1, indent + 1));
+            if value.is_empty() || value.starts_with('#') {
+                pending = Some(child);
+            } else if value.starts_with('|') || value.starts_with('>') {
+                block = Some(indent);
+            }
+            break;
+        }
+    }
+    positions
+}
+
+/// Split a `key: value` line into the unquoted key and the value, `None` if the line has no key.
+fn yaml_key(line: &str) -> Option<(String, &str)> {
+    let (key, rest) = match line.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = line[1..].find(quote)? + 1;
+            (line[1..end].to_string(), line[end + 1..].trim_start_matches(' '))
+        }
+        '{' | '[' | '?' | '&' | '*' | '!' | '|' | '>' => return None,
+        _ => {
+            let end = line.match_indices(':').map(|(index, _)| index)
+                .find(|index| matches!(line[index + 1..].chars().next(), None | Some(' ' | '\t')))?;
+            (line[..end].trim_end().to_string(), &line[end..])
+        }
+    };
+    let value = rest.strip_prefix(':')?;
+    Some((key, value.trim()))
+}
+
+struct Scanner {
+    chars: Vec<char>,
+    index: usize,
+    line: usize,
+    column: usize,
+    positions: HashMap<String, (usize, usize)>,
+}
+
+impl Scanner {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.index += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.bump();
+        }
+    }
+
+    /// Scan a value, returns `None` if the text is malformed.
+    fn value(&mut self, pointer: &str) -> Option<()> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' => {
+                self.bump();
+                self.skip_whitespace();
+                if self.peek()? == '}' {
+                    self.bump();
+                    return Some(());
+                }
+                loop {
+                    self.skip_whitespace();
+                    let position = (self.line, self.column);
+                    let key = self.string()?;
+                    let child = format!("{}/{}", pointer, escape_pointer_token(&key));
+                    self.positions.insert(child.clone(), position);
+                    self.skip_whitespace();
+                    if self.bump()? != ':' {
+                        return None;
+                    }
+                    self.value(&child)?;
+                    self.skip_whitespace();
+                    match self.bump()? {
+                        ',' => continue,
+                        '}' => return Some(()),
+                        _ => return None,
+                    }
+                }
+            }
+            '[' => {
+                self.bump();
+                self.skip_whitespace();
+                if self.peek()? == ']' {
+                    self.bump();
+                    return Some(());
+                }
+                let mut index = 0;
+                loop {
+                    self.skip_whitespace();
+                    let child = format!("{}/{}", pointer, index);
+                    self.positions.insert(child.clone(), (self.line, self.column));
+                    self.value(&child)?;
+                    self.skip_whitespace();
+                    match self.bump()? {
+                        ',' => index += 1,
+                        ']' => return Some(()),
+                        _ => return None,
+                    }
+                }
+            }
+            '"' => self.string().map(|_| ()),
+            _ => {
+                while !matches!(self.peek()?, ',' | '}' | ']' | ' ' | '\t' | '\n' | '\r') {
+                    self.bump();
+                }
+                Some(())
+            }
+        }
+    }
+
+    /// Scan a string and return its unescaped content.
+    fn string(&mut self) -> Option<String> {
+        if self.bump()? != '"' {
+            return None;
+        }
+        let mut result = String::new();
+        loop {
+            match self.bump()? {
+                '"' => return Some(result),
+                '\\' => match self.bump()? {
+                    'n' => result.push('\n'),
+                    't' => result.push('\t'),
+                    'r' => result.push('\r'),
+                    'b' => result.push('\u{8}'),
+                    'f' => result.push('\u{c}'),
+                    'u' => {
+                        let mut code = self.hex()?;
+                        if (0xD800..0xDC00).contains(&code) && self.peek() == Some('\\') {
+                            self.bump();
+                            self.bump();
+                            let low = self.hex()?;
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                        }
+                        result.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    }
+                    c => result.push(c),
+                },
+                c => result.push(c),
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Option<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            code = code * 16 + self.bump()?.to_digit(16)?;
+        }
+        Some(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge::{from_paths_with_provenance, MergeOptions};
+
+    #[test]
+    fn test_json_positions() {
+        let text = "{\n  \"model_details\": {\n    \"name\": 1,\n    \"a/b\\u00e9\": [true, {\"x\": \"}\"}]\n  }\n}\n";
+        let positions = json_positions(text);
+        assert_eq!(positions.get(""), Some(&(1, 1)));
+        assert_eq!(positions.get("/model_details"), Some(&(2, 3)));
+        assert_eq!(positions.get("/model_details/name"), Some(&(3, 5)));
+        assert_eq!(positions.get("/model_details/a~1bé/0"), Some(&(4, 19)));
+        assert_eq!(positions.get("/model_details/a~1bé/1"), Some(&(4, 25)));
+        assert_eq!(positions.get("/model_details/a~1bé/1/x"), Some(&(4, 26)));
+        assert!(json_positions("name: model\n").is_empty());
+    }
+
+    #[test]
+    fn test_yaml_positions() {
+        let text = "# model\nmodel_details:\n  name: Model # the name\n  \"a/b\": |\n    text: no key\n  owners:\n  - name: Jane\n    contact: jane\n  -   name: Joe\nlicenses: [MIT]\ntags:\n  - a\n  -\n    - b\n";
+        let positions = yaml_positions(text);
+        assert_eq!(positions.get(""), Some(&(2, 1)));
+        assert_eq!(positions.get("/model_details"), Some(&(2, 1)));
+        assert_eq!(positions.get("/model_details/name"), Some(&(3, 3)));
+        assert_eq!(positions.get("/model_details/a~1b"), Some(&(4, 3)));
+        assert_eq!(positions.get("/model_details/a~1b/text"), None);
+        assert_eq!(positions.get("/model_details/owners/0"), Some(&(7, 3)));
+        assert_eq!(positions.get("/model_details/owners/0/name"), Some(&(7, 5)));
+        assert_eq!(positions.get("/model_details/owners/0/contact"), Some(&(8, 5)));
+        assert_eq!(positions.get("/model_details/owners/1/name"), Some(&(9, 7)));
+        assert_eq!(positions.get("/licenses"), Some(&(10, 1)));
+        assert_eq!(positions.get("/tags/1"), Some(&(13, 3)));
+        assert_eq!(positions.get("/tags/1/0"), Some(&(14, 5)));
+        // only the front matter of a Markdown file is scanned
+        let positions = yaml_positions("---\nlicense: mit\n---\n# Model\nUsage: see below\n");
+        assert_eq!(positions.get("/license"), Some(&(2, 1)));
+        assert_eq!(positions.get("/Usage"), None);
+    }
+
+    #[test]
+    fn test_locate_root_entries() {
+        let provenance = |sources: &[&str]| -> Provenance {
+            sources.iter().enumerate()
+                .map(|(index, source)| (format!("/field{}", index), Origin { source: source.to_string(), ..Default::default() }))
+                .collect()
+        };
+        let mut report = ValidationReport { entries: vec![ValidationEntry { keyword: "required".to_string(), ..Default::default() }] };
+        report.locate(&provenance(&["defaults.json", "model.json"]));
+        assert_eq!(report.entries[0].location, None);
+        report.locate(&provenance(&["model.json", "model.json"]));
+        assert_eq!(report.entries[0].location.as_ref().map(|location| location.file.as_str()), Some("model.json"));
+    }
+
+    #[test]
+    fn test_locate_and_code_frame() {
+        let file = std::env::temp_dir().join("test_locate_and_code_frame.json");
+        fs::write(&file, "{\n  \"model_details\": {\n    \"name\": 1\n  }\n}\n").unwrap();
+        let file = file.to_string_lossy().to_string();
+        let provenance = Provenance::from([
+            ("/model_details/name".to_string(), Origin { source: file.clone(), ..Default::default() }),
+        ]);
+        let mut report = ValidationReport { entries: vec![
+            ValidationEntry { pointer: "/model_details/name".to_string(), ..Default::default() },
+            ValidationEntry { pointer: "/model_details".to_string(), ..Default::default() },
+            ValidationEntry { pointer: "/other".to_string(), ..Default::default() },
+        ]};
+        report.locate(&provenance);
+        let location = report.entries[0].location.clone().unwrap();
+        assert_eq!(location.to_string(), format!("{}:3:5", file));
+        assert_eq!(location.code_frame().unwrap(), "  |\n2 |   \"model_details\": {\n3 |     \"name\": 1\n  |     ^\n4 |   }\n");
+        assert_eq!(report.entries[1].location.as_ref().and_then(|location| location.line), Some(2));
+        assert_eq!(report.entries[2].location, None);
+    }
+
+    #[test]
+    fn test_locate_moved_array_items() {
+        let dir = std::env::temp_dir().join("test_locate_moved_array_items");
+        fs::create_dir_all(&dir).unwrap();
+        let layers = [
+            ("defaults.json", "{\"model_details\": {\"owners\": [\n  {\"name\": \"Jane\"},\n  {\"name\": \"Joe\"}\n]}}\n"),
+            ("team.json", "{\"model_details\": {\"owners\": [\n  {\"name\": \"Vision\"}\n]}}\n"),
+            ("model.json", "{\"model_details\": {\"owners\": [\n  {\"name\": \"Ann\"},\n  {\"name\": \"Max\",\n   \"contact\": 1}\n]}}\n"),
+        ];
+        for (name, content) in layers {
+            fs::write(dir.join(name), content).unwrap();
+        }
+        let mut options = MergeOptions::default();
+        options.add_array_strategy("append").unwrap();
+        let sources = layers.iter().map(|(name, _)| dir.join(name).to_string_lossy().to_string()).collect();
+        let (_, provenance) = from_paths_with_provenance(sources, &options).unwrap();
+        assert_eq!(provenance["/model_details/owners/4/contact"].source_pointer.as_deref(), Some("/model_details/owners/1/contact"));
+
+        let mut report = ValidationReport { entries: vec![
+            ValidationEntry { pointer: "/model_details/owners/4/contact".to_string(), ..Default::default() },
+            ValidationEntry { pointer: "/model_details/owners/4".to_string(), ..Default::default() },
+            ValidationEntry { pointer: "/model_details/owners/2".to_string(), ..Default::default() },
+        ]};
+        report.locate(&provenance);
+        let locations: Vec<String> = report.entries.iter()
+            .map(|entry| entry.location.as_ref().unwrap().to_string().rsplit(['/', '\\']).next().unwrap().to_string())
+            .collect();
+        assert_eq!(locations, vec!["model.json:4:4", "model.json:3:3", "team.json:2:3"]);
+
+        // items prepended by a later layer keep their position in the earlier layer
+        let mut options = MergeOptions::default();
+        options.add_array_strategy("prepend").unwrap();
+        let sources = layers[..2].iter().map(|(name, _)| dir.join(name).to_string_lossy().to_string()).collect();
+        let (_, provenance) = from_paths_with_provenance(sources, &options).unwrap();
+        assert_eq!(provenance["/model_details/owners/2/name"].source_pointer.as_deref(), Some("/model_details/owners/1/name"));
+        assert_eq!(provenance["/model_details/owners/0/name"].source_pointer, None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,549 @@
+//! # Validation
+//! 
+//! The `validate` module provides functions to validate a model card against a schema.
+//! 
+//! ## Functions
+//! 
+//! The module provides the following functions:
+//! 
+//! - `check_against_schema` - Check a model card against a schema.
+//! - `check_report` - Check a model card against a schema and return a [`ValidationReport`].
+//! - `check_report_with` - Like `check_report`, with a [`Profile`] layered over the schema.
+//! - `validate_against_schema` - Validate a model card against a schema.
+//! - `validate_report` - Validate a model card against a schema and return a [`ValidationReport`].
+//! - `validate_report_with` - Like `validate_report`, resolving schema references with [`SchemaOptions`].
+//! - `score_against_schema` - Score the completeness of a model card against a schema as [`Score`].
+//! - `check_fields` - Find deprecated fields and fields not declared by a schema.
+//! - `check_consistency` - Cross-check the datasets, metric slices, graphics and version date of a model card.
+//! 
+//! ## Schema drafts
+//! 
+//! Schemas are validated according to the draft declared by their `$schema` keyword. Draft 4, 6, 7,
+//! 2019-09 and 2020-12 are supported; schemas without `$schema` are treated as Draft 2020-12.
+//! References to external schemas are not resolved over the network.
+//! 
+//! ## References
+//! 
+//! Schemas can be split into several files. Relative `$ref`s (`common.json#/$defs/owner`) are resolved from
+//! the directory of the schema file and `$id`-based references from the schema files found in that directory
+//! and the directories of the schema search path, see [`SchemaOptions`].
+//! 
+//! ## Profiles
+//! 
+//! A [`Profile`] selects the built-in schema: the Google model card schema (default) or the Hugging Face Hub
+//! metadata schema, which checks the YAML front matter of a `README.md` (see [`crate::utils::DataFormat::Markdown`])
+//! for missing or malformed `license`, `language`, `tags`, `datasets`, `metrics`, `base_model` and `model-index`.
+//! 
+//! Governance profiles (`eu-ai-act`, `nist-ai-rmf` and `internal`) are bundled overlays, which make additional
+//! fields of the Google schema mandatory and add lint rules (see [`crate::lint::check_rules`]).
+//! 
+//! ## Licenses
+//! 
+//! With a [`Profile`], the license identifiers of Google model cards (`/model_details/licenses/*/identifier`)
+//! are checked against the bundled SPDX license list (see [`crate::spdx`]), reporting unknown identifiers as
+//! errors and deprecated identifiers as warnings, both with the suggested identifier.
+//! 
+//! ## Suggestions
+//! 
+//! Fields rejected by `additionalProperties: false` (or `unevaluatedProperties: false`) are reported one entry
+//! per field with the closest property declared at that location as `suggestion`, e.g. `did you mean
+//! 'quantitative_analysis'?` for `quantitive_analysis`.
+//! 
+//! Other schemas, e.g. a custom schema of an organization, are only validated against the schema itself.
+//! 
+//! ## Warnings
+//! 
+//! Entries with [`Severity::Warning`] leave a model card valid: deprecated fields and fields the schema does not
+//! declare (see [`check_fields`]), deprecated license identifiers, soft consistency findings, outdated schema
+//! versions and lint rules on level `warning`. [`ValidationReport::deny_warnings`] turns them into errors.
+//! 
+//! ## Consistency
+//! 
+//! With a [`Profile`], validation reports of Google model cards also contain the findings of
+//! [`check_consistency`] (keyword `consistency`): duplicate dataset names, metric slices and graphic names
+//! referring to unknown datasets, features or metrics, and invalid or future version dates.
+//! 
+//! ## Schema versions
+//! 
+//! With a [`Profile`], a Google model card on a `schema_version` with a newer version available (see
+//! [`crate::migrate`]) is reported with a warning (keyword `schema-version`), suggesting the latest version.
+//! 
+//! ## Reports
+//! 
+//! The `*_report` functions return all problems as [`ValidationReport`] with the JSON pointer, schema keyword,
+//! schema path, severity and message of each entry, which can be rendered as text, JSON or SARIF.
+//! With the [`Provenance`](crate::merge::Provenance) of a merged modelcard, [`ValidationReport::locate`]
+//! attributes every entry to the layer file, line and column which set the invalid value.
+//! 
+//! ## Completeness
+//! 
+//! Validation passes for nearly empty cards, as most fields of the Google schema are optional.
+//! [`score_against_schema`] reports the percentage of optional fields filled per section of the schema.
+//! 
+//! ## Errors
+//! 
+//! The functions will return an error if the model card is not valid against the schema.
+//! The [`anyhow`] crate is used for error handling.
+//! 
+
+use std::path::Path;
+use crate::{
+    assets,
+    merge::{from_paths_with_provenance, MergeOptions},
+    utils::closest_match
+};
+use jsonschema::{error::ValidationErrorKind, BasicOutput, Validator};
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+mod consistency;
+pub use consistency::check_consistency;
+mod fields;
+pub use fields::check_fields;
+mod location;
+pub use location::{json_positions, yaml_positions, SourceLocation};
+mod profile;
+pub use profile::Profile;
+mod resolve;
+pub use resolve::SchemaOptions;
+mod score;
+pub use score::{score_against_schema, Score, SectionScore};
+mod report;
+pub use report::{ReportFormat, Severity, ValidationEntry, ValidationReport};
+
+/// Check a model card against a schema.
+/// 
+/// The function takes a path to a model card and a path to a schema and checks the model card against the schema.
+/// 
+/// ## Arguments
+/// 
+/// - `path` - A path to a model card or a directory containing a model card.
+/// - `modelcard` - A path to a schema file.
+/// 
+/// ## Returns
+/// 
+/// The function returns a `Result` with a boolean indicating whether the model card is valid against the schema.
+/// 
+/// ## Errors
+/// 
+/// The function will return an error if the model card is not valid against the schema.
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use std::path::Path;
+/// use modelcards::validate::check_against_schema;
+/// 
+/// let schema_path = Path::new("tests/schemas/my_schema.json");
+/// let data_path = Path::new("tests/data/sample.json");
+/// let result = check_against_schema(schema_path, data_path).unwrap();
+/// assert_eq!(result, true);
+/// ```
+/// 
+pub fn check_against_schema(path: &Path, modelcard: &Path) -> Result<bool> {
+    let report = check_report(path, modelcard)?;
+    if !report.is_valid() {
+        bail!("Validation failed:\n{}", report);
+    }
+    Ok(true)
+}
+
+/// Check a model card against a schema and return all problems found.
+/// 
+/// Works like [`check_against_schema`], but returns a [`ValidationReport`] instead of failing if the model card is invalid.
+/// References in the model card are interpolated (with `project_dir` set to the project directory) and the entries
+/// are located in the layer files of the model card.
+/// 
+/// ## Errors
+/// 
+/// The function will return an error if the schema or the model card can not be loaded or the schema is invalid.
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use std::path::Path;
+/// use modelcards::validate::check_report;
+/// 
+/// let report = check_report(Path::new("."), Path::new("sample.json")).unwrap();
+/// for entry in report.errors() {
+///     println!("{}: {}", entry.pointer, entry.message);
+/// }
+/// ```
+/// 
+pub fn check_report(path: &Path, modelcard: &Path) -> Result<ValidationReport> {
+    check_report_with(path, modelcard, &[], Profile::Google, &MergeOptions { interpolate: true, ..Default::default() })
+}
+
+/// Check a model card against a schema with a [`Profile`] layered over it and return all problems found.
+/// 
+/// Works like [`check_report`], the rules of a governance profile are checked as well. Schemas referenced by the
+/// project schema are also searched in the directories of `schema_path`, relative to the project directory.
+/// The layers of the model card are merged with the given options, e.g. rejecting type conflicts with `strict`.
+/// 
+/// ## Errors
+/// 
+/// The function will return an error if the schema or the model card can not be loaded or the schema is invalid.
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use std::path::Path;
+/// use modelcards::{merge::MergeOptions, validate::{check_report_with, Profile}};
+/// 
+/// let options = MergeOptions { strict: true, interpolate: true, ..Default::default() };
+/// let report = check_report_with(Path::new("."), Path::new("sample.json"), &["schemas".to_string()], Profile::EuAiAct, &options).unwrap();
+/// println!("{}", report);
+/// ```
+/// 
+pub fn check_report_with(path: &Path, modelcard: &Path, schema_path: &[String], profile: Profile, options: &MergeOptions) -> Result<ValidationReport> {
+
+    if !path.exists() {
+        bail!("Path does not exist: {:?}", path);
+    }
+
+    let (schema_file, project_dir) = if path.is_dir() {
+        //TODO: get schema from config
+        (path.join("schema/modelcard.schema.json"), path)
+    } else {
+        (path.to_path_buf(), path.parent().unwrap_or(Path::new("")))
+    };
+    let schema_options = SchemaOptions::from_file(schema_file)
+        .with_search_path(schema_path.iter().map(|dir| project_dir.join(dir)));
+    let schema = schema_options.load()?;
+    // references of the project data are resolved like by the build
+    let mut merge_options = options.clone();
+    if path.is_dir() {
+        merge_options.variables.insert("project_dir".to_string(), path.display().to_string());
+    }
+    let (modelcard, provenance) = from_paths_with_provenance(vec![modelcard.to_string_lossy().to_string()], &merge_options)?;
+
+    let mut report = profile.validate(&modelcard, Some(schema), &schema_options)?;
+    report.locate(&provenance);
+    Ok(report)
+}
+
+/// Validate a model card against a schema.
+/// 
+/// The function takes a model card and a schema and validates the model card against the schema.
+/// 
+/// ## Arguments
+/// 
+/// - `modelcard` - A JSON object representing the model card.
+/// - `schema` - A JSON object representing the schema.
+/// 
+/// ## Returns
+/// 
+/// The function returns a `Result` with a boolean indicating whether the model card is valid against the schema.
+/// 
+/// ## Errors
+/// 
+/// The function will return an error if the model card is not valid against the schema.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::validate::validate_against_schema;
+/// 
+/// let modelcard = json!({
+///     "name": "Model Name",
+///     "schema_version": "0.0.2"
+/// });
+/// let schema = json!({
+///     "type": "object",
+///     "properties": {
+///         "name": {
+///             "type": "string"
+///         },
+///         "schema_version": {
+///             "type": "string"
+///         }
+///     },
+///     "required": ["name", "schema_version"]
+/// });
+/// let result = validate_against_schema(modelcard, Some(schema)).unwrap();
+/// assert_eq!(result, true);
+/// ```
+/// 
+pub fn validate_against_schema(modelcard: Value, schema: Option<Value>) -> Result<bool> {
+    let report = validate_report(&modelcard, schema)?;
+    if !report.is_valid() {
+        bail!("Validation failed:\n{}", report);
+    }
+    Ok(true)
+}
+
+/// Validate a model card against a schema and return all problems found.
+/// 
+/// Works like [`validate_against_schema`], but returns a [`ValidationReport`] instead of failing if the model card is invalid.
+/// Errors of failed `anyOf` and `oneOf` keywords hold the errors of each alternative as sub errors.
+/// 
+/// ## Errors
+/// 
+/// The function will return an error if the schema is invalid.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::validate::validate_report;
+/// 
+/// let schema = json!({
+///     "type": "object",
+///     "properties": {"name": {"type": "string"}},
+///     "required": ["name", "schema_version"]
+/// });
+/// let report = validate_report(&json!({"name": 1}), Some(schema)).unwrap();
+/// assert!(!report.is_valid());
+/// assert_eq!(report.entries[0].pointer, "/name");
+/// assert_eq!(report.entries[0].keyword, "type");
+/// assert_eq!(report.entries[1].keyword, "required");
+/// ```
+/// 
+pub fn validate_report(modelcard: &Value, schema: Option<Value>) -> Result<ValidationReport> {
+    validate_report_with(modelcard, schema, &SchemaOptions::default())
+}
+
+/// Validate a model card against a schema split into several files and return all problems found.
+/// 
+/// Works like [`validate_report`], but resolves the references of the schema to other schema files
+/// from the directory of [`SchemaOptions::file`] and the [`SchemaOptions::search_path`].
+/// 
+/// ## Errors
+/// 
+/// The function will return an error if the schema is invalid or a referenced schema can not be found.
+/// 
+/// ## Example
+/// 
+/// ```rust,no_run
+/// use serde_json::json;
+/// use modelcards::validate::{validate_report_with, SchemaOptions};
+/// 
+/// let options = SchemaOptions::from_file("schemas/modelcard.json").with_search_path(["schemas/shared"]);
+/// let report = validate_report_with(&json!({"name": "My Model"}), Some(options.load().unwrap()), &options).unwrap();
+/// assert!(report.is_valid());
+/// ```
+/// 
+pub fn validate_report_with(modelcard: &Value, schema: Option<Value>, options: &SchemaOptions) -> Result<ValidationReport> {
+
+    let schema = match schema {
+        Some(s) => s,
+        None => serde_json::from_str(assets::schema::get_schema())?,
+    };
+
+    // the draft is selected by the `$schema` keyword of the schema
+    let validator = options.validator(&schema)?;
+    let mut report = ValidationReport::default();
+    // the errors of the alternatives of `anyOf` and `oneOf` are only part of the detailed output
+    let mut details: Option<Vec<ValidationEntry>> = None;
+    for e in validator.iter_errors(modelcard) {
+        let mut entry = ValidationEntry {
+            pointer: e.instance_path.to_string(),
+            schema_path: e.schema_path.to_string(),
+            message: e.to_string(),
+            ..Default::default()
+        };
+        entry.keyword = keyword_of(&entry.schema_path);
+        if matches!(e.kind, ValidationErrorKind::AnyOf | ValidationErrorKind::OneOfNotValid) {
+            let details = details.get_or_insert_with(|| detailed_errors(&validator, modelcard));
+            let prefix = format!("{}/", entry.schema_path);
+            entry.sub_errors = details.iter()
+                .filter(|detail| detail.schema_path.starts_with(&prefix) && detail.pointer.starts_with(&entry.pointer))
+                .cloned()
+                .collect();
+        }
+        let unexpected = match &e.kind {
+            ValidationErrorKind::AdditionalProperties { unexpected } => Some(("Additional", unexpected)),
+            ValidationErrorKind::UnevaluatedProperties { unexpected } => Some(("Unevaluated", unexpected)),
+            _ => None,
+        };
+        if let Some((kind, unexpected)) = unexpected {
+            // one entry per unexpected field, each with the declared property it is probably meant to be
+            for field in unexpected {
+                let mut entry = entry.clone();
+                entry.message = format!("{} properties are not allowed ('{}' was unexpected)", kind, field);
+                entry.suggestion = suggest_property(&schema, &entry.schema_path, modelcard.pointer(&entry.pointer), field);
+                if let Some(suggestion) = &entry.suggestion {
+                    entry.message = format!("{}, did you mean '{}'?", entry.message, suggestion);
+                }
+                report.entries.push(entry);
+            }
+            continue;
+        }
+        report.entries.push(entry);
+    }
+    report.entries.extend(check_fields(modelcard, &schema));
+
+    Ok(report)
+}
+
+/// The declared property closest to an unexpected field, from the properties of the schema node owning the
+/// failed keyword, leaving out the properties already set.
+fn suggest_property(schema: &Value, schema_path: &str, instance: Option<&Value>, field: &str) -> Option<String> {
+    let mut node = schema;
+    let tokens: Vec<String> = schema_path.split('/').skip(1).map(|token| token.replace("~1", "/").replace("~0", "~")).collect();
+    // the last token is the failed keyword, its siblings declare the properties
+    for token in &tokens[..tokens.len().saturating_sub(1)] {
+        node = match token.as_str() {
+            "$ref" => score::resolve(schema, node),
+            token => match node {
+                Value::Array(items) => items.get(token.parse::<usize>().ok()?)?,
+                node => node.get(token)?,
+            },
+        };
+    }
+    let properties = score::resolve(schema, node).get("properties")?.as_object()?;
+    let candidates = properties.keys()
+        .map(String::as_str)
+        .filter(|property| instance.and_then(|instance| instance.get(*property)).is_none());
+    closest_match(field, candidates).map(String::from)
+}
+
+fn keyword_of(schema_path: &str) -> String {
+    schema_path.rsplit('/').next().unwrap_or_default().to_string()
+}
+
+fn detailed_errors(validator: &Validator, modelcard: &Value) -> Vec<ValidationEntry> {
+    match validator.apply(modelcard).basic() {
+        BasicOutput::Valid(_) => vec![],
+        BasicOutput::Invalid(units) => units.into_iter()
+            .map(|unit| {
+                let schema_path = unit.keyword_location().to_string();
+                ValidationEntry {
+                    pointer: unit.instance_location().to_string(),
+                    keyword: keyword_of(&schema_path),
+                    schema_path,
+                    message: unit.error_description().to_string(),
+                    ..Default::default()
+                }
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::assets::schema;
+    use crate::utils::create_file;
+
+    use std::{
+        env::temp_dir,
+        fs::{create_dir, remove_dir_all},
+        path::{PathBuf, Path}
+    };
+    use anyhow::Result;
+
+    fn get_temp_dir(path: &str, create: bool) -> PathBuf {
+        let mut dir = temp_dir();
+        dir.push(path);
+        if dir.exists() {
+            remove_dir_all(&dir).expect("Could not free test directory");
+        }
+        if create {
+            create_dir(&dir).expect("Could not create test directory");
+        }
+        dir
+    }
+
+    fn populate_modelcards_dir(path: &Path) -> Result<()> {
+        create_file(&path.join("sample.json"), schema::get_sample())?;
+        create_dir(path.join("schema"))?;
+        create_file(&path.join("schema/modelcard.schema.json"), schema::get_schema())?;
+        Ok(())
+    }
+
+    #[test]
+    fn check_valid_against_schema() {
+        let dir = get_temp_dir("test_check_against_schema", true);
+        populate_modelcards_dir(&dir).expect("Could not populate modelcards directory");
+        assert!(check_against_schema(&dir, &dir.join("sample.json")).is_ok());
+    }
+
+    #[test]
+    fn validate_invalid_against_bundled_schema() {
+        let modelcard = serde_json::json!({"model_details": {"name": 1}, "unknown": true});
+        let error = format!("{:?}", validate_against_schema(modelcard, None).unwrap_err());
+        assert!(error.contains("error [type] /model_details/name"), "{}", error);
+        assert!(error.contains("error [additionalProperties] (root): "), "{}", error);
+    }
+
+    #[test]
+    fn validate_report_suggests_misspelled_fields() {
+        let mut modelcard: Value = serde_json::from_str(schema::get_sample()).unwrap();
+        let analysis = modelcard.as_object_mut().unwrap().remove("quantitative_analysis").unwrap();
+        modelcard["quantitive_analysis"] = analysis;
+        modelcard["custom"] = serde_json::json!({});
+        let report = validate_report(&modelcard, None).unwrap();
+        let entries: Vec<(&str, &str, Option<&str>)> = report.errors()
+            .map(|entry| (entry.pointer.as_str(), entry.message.as_str(), entry.suggestion.as_deref()))
+            .collect();
+        assert_eq!(entries, vec![
+            ("", "Additional properties are not allowed ('custom' was unexpected)", None),
+            ("", "Additional properties are not allowed ('quantitive_analysis' was unexpected), did you mean 'quantitative_analysis'?", Some("quantitative_analysis")),
+        ]);
+
+        // properties of referenced schemas are suggested as well
+        let schema = serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "properties": {"owners": {"type": "array", "items": {"$ref": "#/$defs/owner"}}},
+            "$defs": {"owner": {"type": "object", "properties": {"name": {}, "contact": {}}, "unevaluatedProperties": false}}
+        });
+        let report = validate_report(&serde_json::json!({"owners": [{"name": "Jane", "contcat": "jane@example.org"}]}), Some(schema)).unwrap();
+        assert_eq!(report.entries.len(), 1, "{}", report);
+        assert_eq!(report.entries[0].pointer, "/owners/0");
+        assert_eq!(report.entries[0].suggestion.as_deref(), Some("contact"));
+    }
+
+    #[test]
+    fn validate_report_with_any_of_sub_errors() {
+        let schema = serde_json::json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "definitions": {"name": {"type": "string"}},
+            "properties": {"owner": {"anyOf": [{"$ref": "#/definitions/name"}, {"type": "object", "required": ["name"]}]}}
+        });
+        let report = validate_report(&serde_json::json!({"owner": 1}), Some(schema)).unwrap();
+        assert_eq!(report.entries.len(), 1);
+        let entry = &report.entries[0];
+        assert_eq!((entry.pointer.as_str(), entry.keyword.as_str(), entry.schema_path.as_str()), ("/owner", "anyOf", "/properties/owner/anyOf"));
+        let sub_errors: Vec<&str> = entry.sub_errors.iter().map(|e| e.schema_path.as_str()).collect();
+        assert_eq!(sub_errors, vec!["/properties/owner/anyOf/0/$ref/type", "/properties/owner/anyOf/1/type"]);
+    }
+
+    #[test]
+    fn validate_with_draft_2020_12_keywords() {
+        let schema = serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$defs": {"owner": {"type": "object", "properties": {"name": {"type": "string"}}}},
+            "type": "object",
+            "properties": {"owner": {"$ref": "#/$defs/owner"}, "license": {"type": "string"}},
+            "dependentRequired": {"license": ["owner"]},
+            "unevaluatedProperties": false
+        });
+        assert!(validate_against_schema(serde_json::json!({"owner": {"name": "Jane"}, "license": "MIT"}), Some(schema.clone())).is_ok());
+        assert!(validate_against_schema(serde_json::json!({"owner": {"name": 1}}), Some(schema.clone())).is_err());
+        assert!(validate_against_schema(serde_json::json!({"license": "MIT"}), Some(schema.clone())).is_err());
+        assert!(validate_against_schema(serde_json::json!({"owner": {}, "extra": 1}), Some(schema)).is_err());
+    }
+
+    #[test]
+    fn validate_with_draft_4_keywords() {
+        // draft 4 uses a boolean `exclusiveMaximum` together with `maximum`
+        let schema = serde_json::json!({
+            "$schema": "http://json-schema.org/draft-04/schema#",
+            "type": "object",
+            "properties": {"accuracy": {"type": "number", "maximum": 1, "exclusiveMaximum": true}}
+        });
+        assert!(validate_against_schema(serde_json::json!({"accuracy": 0.9}), Some(schema.clone())).is_ok());
+        assert!(validate_against_schema(serde_json::json!({"accuracy": 1}), Some(schema)).is_err());
+    }
+
+    #[test]
+    fn check_valid_against_missing_schema() {
+        let dir = get_temp_dir("test_check_missing_schema", true);
+        populate_modelcards_dir(&dir).expect("Could not populate modelcards directory");
+        //force error with missing schema
+        assert!(check_against_schema(&dir, &dir.join("sample_2.json")).is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,289 @@
+//! Validation profiles.
+//!
+//! A profile selects the built-in schema a modelcard is validated against: the Google model card schema for
+//! the modelcard data of a project, or the Hugging Face Hub metadata schema for the YAML front matter of a
+//! `README.md` published to the Hub.
+//!
+//! Governance profiles (EU AI Act, NIST AI RMF and the internal policy) are bundled overlays of the Google
+//! schema. An overlay makes additional fields mandatory (its `required` lists are merged into the schema)
+//! and adds lint rules, which are checked with [`check_rules`].
+//!
+//! Model cards of the Google schema (the bundled schema or a copy with its `$id`, optionally with a governance
+//! overlay) are also checked for license identifiers, consistency and outdated schema versions, see
+//! [`Profile::validate`]. Cards of other schemas are only validated against their schema.
+
+use std::{fmt, str::FromStr};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    assets,
+    lint::{check_rules, Rule},
+    merge::{merge_with, ArrayStrategy, MergeOptions},
+    migrate::{builtin_migrations, check_version},
+    spdx::check_licenses
+};
+use super::{check_consistency, validate_report_with, SchemaOptions, ValidationReport};
+
+/// Built-in schema to validate a modelcard against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum Profile {
+    /// The Google model card schema (default).
+    #[default]
+    #[serde(rename = "google")]
+    Google,
+    /// The Hugging Face Hub model card metadata (`language`, `license`, `tags`, `datasets`, `metrics`,
+    /// `base_model`, `model-index`, ...).
+    #[serde(rename = "huggingface", alias = "hf")]
+    HuggingFace,
+    /// Technical documentation of the EU AI Act (Annex IV) over the Google schema.
+    #[serde(rename = "eu-ai-act")]
+    EuAiAct,
+    /// NIST AI Risk Management Framework over the Google schema.
+    #[serde(rename = "nist-ai-rmf")]
+    NistAiRmf,
+    /// Internal model card policy over the Google schema.
+    #[serde(rename = "internal")]
+    Internal,
+}
+
+/// A bundled governance overlay.
+#[derive(Debug, Deserialize)]
+struct Overlay {
+    /// Schema merged into the base schema, `required` lists are joined.
+    schema: Value,
+    /// Lint rules checked in addition to the schema.
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+impl Profile {
+    /// The schema of the profile.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use modelcards::validate::Profile;
+    ///
+    /// let schema = Profile::HuggingFace.schema().unwrap();
+    /// assert_eq!(schema["required"][0], "license");
+    /// let schema = Profile::EuAiAct.schema().unwrap();
+    /// assert!(schema["definitions"]["owner"]["required"].as_array().unwrap().contains(&"contact".into()));
+    /// ```
+    pub fn schema(&self) -> Result<Value> {
+        self.apply(serde_json::from_str(assets::schema::get_schema())?)
+    }
+
+    /// Layer the profile over a schema.
+    ///
+    /// Governance profiles merge their overlay into the schema, the Hugging Face profile replaces it,
+    /// as it validates the metadata of a Hub `README.md` instead of a Google model card.
+    pub fn apply(&self, mut schema: Value) -> Result<Value> {
+        match self {
+            Profile::Google => {}
+            Profile::HuggingFace => schema = serde_json::from_str(assets::schema::get_huggingface_schema())?,
+            _ => {
+                let options = MergeOptions { arrays: ArrayStrategy::Union, ..Default::default() };
+                merge_with(&mut schema, self.overlay()?.schema, &options);
+            }
+        }
+        Ok(schema)
+    }
+
+    /// The lint rules of the profile, empty for the Google and Hugging Face profiles.
+    pub fn rules(&self) -> Result<Vec<Rule>> {
+        match self {
+            Profile::Google | Profile::HuggingFace => Ok(vec![]),
+            _ => Ok(self.overlay()?.rules),
+        }
+    }
+
+    fn overlay(&self) -> Result<Overlay> {
+        let name = self.to_string();
+        let overlay = assets::schema::get_profile(&name).with_context(|| format!("No overlay for profile '{}'", name))?;
+        serde_json::from_str(overlay).with_context(|| format!("Invalid overlay of profile '{}'", name))
+    }
+
+    /// Validate a model card against a schema with the profile layered over it and check the rules of the profile.
+    ///
+    /// If no schema is given, the built-in schema of the profile is used. Model cards of the Google schema are
+    /// checked for unknown license identifiers (see [`check_licenses`]), inconsistencies (see [`check_consistency`])
+    /// and outdated schema versions (see [`check_version`]) as well.
+    ///
+    /// ## Errors
+    ///
+    /// The function will return an error if the schema or a rule of the profile is invalid.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use modelcards::validate::{Profile, SchemaOptions};
+    ///
+    /// let modelcard = json!({"model_details": {"name": "My Model"}});
+    /// assert!(Profile::Google.validate(&modelcard, None, &SchemaOptions::default()).unwrap().is_valid());
+    /// let report = Profile::EuAiAct.validate(&modelcard, None, &SchemaOptions::default()).unwrap();
+    /// assert!(report.errors().any(|entry| entry.pointer == "/model_details" && entry.message.contains("overview")));
+    /// ```
+    pub fn validate(&self, modelcard: &Value, schema: Option<Value>, options: &SchemaOptions) -> Result<ValidationReport> {
+        let schema = match schema {
+            Some(schema) => self.apply(schema)?,
+            None => self.schema()?,
+        };
+        let google = is_google_schema(&schema)?;
+        let mut report = validate_report_with(modelcard, Some(schema), options)?;
+        if google {
+            report.entries.extend(check_licenses(modelcard));
+            report.entries.extend(check_consistency(modelcard));
+            report.entries.extend(check_version(modelcard, builtin_migrations()));
+        }
+        report.entries.extend(check_rules(modelcard, &self.rules()?)?.entries);
+        Ok(report)
+    }
+}
+
+/// Whether a schema is the Google model card schema, identified by its `$id`.
+fn is_google_schema(schema: &Value) -> Result<bool> {
+    let google: Value = serde_json::from_str(assets::schema::get_schema())?;
+    Ok(schema.get("$id").is_some_and(|id| Some(id) == google.get("$id")))
+}
+
+impl FromStr for Profile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "google" => Ok(Profile::Google),
+            "huggingface" | "hf" => Ok(Profile::HuggingFace),
+            "eu-ai-act" => Ok(Profile::EuAiAct),
+            "nist-ai-rmf" => Ok(Profile::NistAiRmf),
+            "internal" => Ok(Profile::Internal),
+            other => bail!("Unknown profile '{}' (expected google, huggingface, eu-ai-act, nist-ai-rmf or internal)", other),
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Profile::Google => write!(f, "google"),
+            Profile::HuggingFace => write!(f, "huggingface"),
+            Profile::EuAiAct => write!(f, "eu-ai-act"),
+            Profile::NistAiRmf => write!(f, "nist-ai-rmf"),
+            Profile::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::{utils::{parse_data, DataFormat}, validate::validate_report};
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!("HF".parse::<Profile>().unwrap(), Profile::HuggingFace);
+        assert_eq!(Profile::HuggingFace.to_string(), "huggingface");
+        assert!("openai".parse::<Profile>().is_err());
+        for profile in [Profile::Google, Profile::HuggingFace, Profile::EuAiAct, Profile::NistAiRmf, Profile::Internal] {
+            assert_eq!(profile.to_string().parse::<Profile>().unwrap(), profile);
+            assert_eq!(serde_json::from_value::<Profile>(json!(profile.to_string())).unwrap(), profile);
+        }
+    }
+
+    #[test]
+    fn test_governance_overlays() {
+        let google: Value = serde_json::from_str(assets::schema::get_schema()).unwrap();
+        for profile in [Profile::EuAiAct, Profile::NistAiRmf, Profile::Internal] {
+            let schema = profile.schema().unwrap();
+            assert_ne!(schema, google, "{}", profile);
+            assert!(jsonschema::validator_for(&schema).is_ok(), "{}", profile);
+            assert!(!profile.rules().unwrap().is_empty(), "{}", profile);
+            let report = profile.validate(&json!({}), None, &SchemaOptions::default()).unwrap();
+            assert!(!report.is_valid(), "{}", profile);
+        }
+        assert!(Profile::Google.rules().unwrap().is_empty());
+        // overlays join the `required` lists of the base schema
+        let schema = Profile::Internal.apply(json!({"properties": {"model_details": {"required": ["path"]}}})).unwrap();
+        let required = schema["properties"]["model_details"]["required"].as_array().unwrap();
+        assert_eq!(required[0], "path");
+        assert!(required.contains(&json!("owners")));
+    }
+
+    #[test]
+    fn test_governance_profile_rules() {
+        let mut modelcard: Value = serde_json::from_str(assets::schema::get_sample()).unwrap();
+        modelcard["quantitative_analysis"]["performance_metrics"] = json!([{"type": "accuracy", "value": 0.9}]);
+        let report = Profile::NistAiRmf.validate(&modelcard, None, &SchemaOptions::default()).unwrap();
+        let keywords: Vec<&str> = report.entries.iter().map(|entry| entry.keyword.as_str()).collect();
+        assert!(keywords.contains(&"nist-ai-rmf-metric-slice"), "{:?}", keywords);
+        assert!(keywords.contains(&"nist-ai-rmf-metric-confidence-interval"), "{:?}", keywords);
+        assert!(!keywords.iter().any(|keyword| keyword.starts_with("eu-ai-act")));
+    }
+
+    #[test]
+    fn test_google_card_checks() {
+        let modelcard = json!({
+            "schema_version": "0.0.2",
+            "model_details": {"licenses": [{"identifier": "Apache 2.0"}], "version": {"date": "9999-12-31"}}
+        });
+        let report = Profile::Google.validate(&modelcard, None, &SchemaOptions::default()).unwrap();
+        let keywords: Vec<&str> = report.entries.iter().map(|entry| entry.keyword.as_str()).collect();
+        for keyword in ["spdx", "consistency", "schema-version"] {
+            assert!(keywords.contains(&keyword), "{} not in {:?}", keyword, keywords);
+        }
+
+        // a custom schema without these fields is only validated against itself
+        let schema = json!({"$id": "https://example.org/modelcard.json", "type": "object", "properties": {"schema_version": {}, "model_details": {"type": "object"}}});
+        let report = Profile::Google.validate(&modelcard, Some(schema), &SchemaOptions::default()).unwrap();
+        assert!(report.is_valid() && !report.has_warnings(), "{}", report);
+    }
+
+    #[test]
+    fn test_huggingface_metadata() {
+        let schema = Profile::HuggingFace.schema().unwrap();
+        let readme = r#"---
+language:
+- en
+- fr-CA
+license: apache-2.0
+tags: [audio, speech]
+datasets: mozilla-foundation/common_voice_11_0
+metrics: [wer]
+base_model: openai/whisper-small
+model-index:
+- name: whisper-small-fr
+  results:
+  - task: {type: automatic-speech-recognition, name: Speech Recognition}
+    dataset: {type: common_voice, name: Common Voice (French), split: test}
+    metrics:
+    - {type: wer, value: 20.9, name: Test WER}
+    source: {name: Leaderboard, url: "https://huggingface.co/spaces/leaderboard"}
+---
+# whisper-small-fr
+"#;
+        let metadata = parse_data(readme, DataFormat::Markdown).unwrap();
+        let report = validate_report(&metadata, Some(schema.clone())).unwrap();
+        assert!(report.is_valid(), "{}", report);
+
+        let metadata = json!({
+            "language": "English",
+            "license": "other",
+            "tags": "audio",
+            "base_model": "not a model id",
+            "model-index": [{"name": "model", "results": [{"task": {}, "dataset": {"type": "common_voice"}, "metrics": [{"type": "wer"}]}]}]
+        });
+        let report = validate_report(&metadata, Some(schema.clone())).unwrap();
+        let pointers: Vec<&str> = report.entries.iter().map(|entry| entry.pointer.as_str()).collect();
+        for pointer in ["", "/language", "/tags", "/base_model", "/model-index/0/results/0/task", "/model-index/0/results/0/dataset", "/model-index/0/results/0/metrics/0"] {
+            assert!(pointers.contains(&pointer), "{} not in {:?}", pointer, pointers);
+        }
+        assert!(report.entries.iter().any(|entry| entry.pointer.is_empty() && entry.message.contains("license_name")));
+
+        let report = validate_report(&json!({"tags": ["audio"]}), Some(schema)).unwrap();
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].keyword, "required");
+    }
+}
@@ -0,0 +1,334 @@
+//! Structured results of a validation.
+//!
+//! A [`ValidationReport`] holds one [`ValidationEntry`] per problem found, with the JSON pointer of the
+//! value, the schema keyword and location that failed, a [`Severity`] and a message. Failed `anyOf` and
+//! `oneOf` keywords carry the errors of their alternatives as sub errors.
+//!
+//! Entries can be attributed to the layer file and line which set the invalid value with
+//! [`ValidationReport::locate`], the text output then shows a code frame of that line.
+//!
+//! Reports can be rendered as text for the terminal, as JSON for tooling or as
+//! [SARIF](https://sarifweb.azurewebsites.net/) for code scanning in CI (see [`ReportFormat`]).
+
+use std::{collections::BTreeSet, fmt, str::FromStr};
+use serde::Serialize;
+use serde_json::{json, Value};
+use anyhow::{bail, Result};
+
+use super::SourceLocation;
+
+/// Severity of a validation entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The modelcard is invalid (default).
+    #[default]
+    Error,
+    /// The modelcard is valid, but should be fixed.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A single problem found by a validation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationEntry {
+    /// JSON pointer of the invalid value in the modelcard.
+    pub pointer: String,
+    /// The schema keyword that failed, e.g. `type` or `required`, or the id of the failed lint rule.
+    pub keyword: String,
+    /// JSON pointer of the failed keyword in the schema.
+    pub schema_path: String,
+    /// Severity of the problem.
+    pub severity: Severity,
+    /// Description of the problem.
+    pub message: String,
+    /// Errors of the alternatives of a failed `anyOf` or `oneOf` keyword.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sub_errors: Vec<ValidationEntry>,
+    /// The value probably meant, e.g. the correct spelling of a license identifier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// The source file (and line) which set the invalid value, see [`ValidationReport::locate`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+}
+
+impl ValidationEntry {
+    fn write_text(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() { "(root)" } else { &self.pointer };
+        writeln!(f, "{:indent$}{} [{}] {}: {}", "", self.severity, self.keyword, pointer, self.message, indent = indent)?;
+        if let Some(location) = &self.location {
+            writeln!(f, "{:indent$}  --> {}", "", location, indent = indent)?;
+            // code frames are only shown for top level entries to keep the alternatives of `anyOf` short
+            if let Some(frame) = location.code_frame().filter(|_| indent == 0) {
+                for line in frame.lines() {
+                    writeln!(f, "  {}", line)?;
+                }
+            }
+        }
+        for sub_error in &self.sub_errors {
+            sub_error.write_text(f, indent + 4)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ValidationEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_text(f, 0)
+    }
+}
+
+/// The result of validating a modelcard.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    /// The problems found, in the order they were found.
+    pub entries: Vec<ValidationEntry>,
+}
+
+impl ValidationReport {
+    /// Whether the modelcard is valid, i.e. the report contains no errors.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Whether the report contains warnings.
+    pub fn has_warnings(&self) -> bool {
+        self.warnings().next().is_some()
+    }
+
+    /// Promote all warnings to errors, e.g. to fail a build on deprecated or unknown fields.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use modelcards::validate::{Severity, ValidationEntry, ValidationReport};
+    ///
+    /// let mut report = ValidationReport { entries: vec![ValidationEntry { severity: Severity::Warning, ..Default::default() }] };
+    /// assert!(report.is_valid());
+    /// report.deny_warnings();
+    /// assert!(!report.is_valid() && !report.has_warnings());
+    /// ```
+    pub fn deny_warnings(&mut self) {
+        for entry in &mut self.entries {
+            entry.severity = Severity::Error;
+        }
+    }
+
+    /// All entries with severity [`Severity::Error`].
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationEntry> {
+        self.entries.iter().filter(|entry| entry.severity == Severity::Error)
+    }
+
+    /// All entries with severity [`Severity::Warning`].
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationEntry> {
+        self.entries.iter().filter(|entry| entry.severity == Severity::Warning)
+    }
+
+    /// Render the report as JSON.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "valid": self.is_valid(),
+            "errors": self.errors().count(),
+            "warnings": self.warnings().count(),
+            "entries": self.entries,
+        })
+    }
+
+    /// Render the report as SARIF 2.1.0 log, reporting all entries for the given artifact (the validated file).
+    pub fn to_sarif(&self, artifact: Option<&str>) -> Value {
+        let rules: BTreeSet<&str> = self.entries.iter().map(|entry| entry.keyword.as_str()).collect();
+        let results: Vec<Value> = self.entries.iter().map(|entry| {
+            let mut location = json!({
+                "logicalLocations": [{"fullyQualifiedName": entry.pointer, "kind": "member"}]
+            });
+            if let Some(source) = &entry.location {
+                let mut physical = json!({"artifactLocation": {"uri": source.file}});
+                if let (Some(line), Some(column)) = (source.line, source.column) {
+                    physical["region"] = json!({"startLine": line, "startColumn": column});
+                }
+                location["physicalLocation"] = physical;
+            } else if let Some(artifact) = artifact {
+                location["physicalLocation"] = json!({"artifactLocation": {"uri": artifact}});
+            }
+            let mut message = entry.message.clone();
+            for sub_error in &entry.sub_errors {
+                message.push_str(&format!("\n- {}: {}", sub_error.pointer, sub_error.message));
+            }
+            let mut properties = json!({"schemaPath": entry.schema_path});
+            if let Some(suggestion) = &entry.suggestion {
+                properties["suggestion"] = json!(suggestion);
+            }
+            json!({
+                "ruleId": entry.keyword,
+                "level": entry.severity.to_string(),
+                "message": {"text": message},
+                "locations": [location],
+                "properties": properties,
+            })
+        }).collect();
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {"driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules.into_iter().map(|rule| json!({"id": rule})).collect::<Vec<Value>>(),
+                }},
+                "results": results,
+            }]
+        })
+    }
+
+    /// Render the report in the given format.
+    pub fn render(&self, format: ReportFormat, artifact: Option<&str>) -> String {
+        match format {
+            ReportFormat::Text => self.to_string(),
+            ReportFormat::Json => serde_json::to_string_pretty(&self.to_json()).unwrap_or_default(),
+            ReportFormat::Sarif => serde_json::to_string_pretty(&self.to_sarif(artifact)).unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            write!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Output format of a [`ValidationReport`].
+///
+/// The format can be parsed from its name: `text`, `json` or `sarif`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// One line per entry for the terminal (default).
+    #[default]
+    Text,
+    /// The report as JSON object.
+    Json,
+    /// A SARIF 2.1.0 log for code scanning tools.
+    Sarif,
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            "sarif" => Ok(ReportFormat::Sarif),
+            other => bail!("Unknown report format '{}' (expected text, json or sarif)", other),
+        }
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportFormat::Text => write!(f, "text"),
+            ReportFormat::Json => write!(f, "json"),
+            ReportFormat::Sarif => write!(f, "sarif"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> ValidationReport {
+        ValidationReport { entries: vec![
+            ValidationEntry {
+                pointer: "/model_details/name".to_string(),
+                keyword: "type".to_string(),
+                schema_path: "/properties/model_details/properties/name/type".to_string(),
+                message: "1 is not of type \"string\"".to_string(),
+                ..Default::default()
+            },
+            ValidationEntry {
+                pointer: "/license".to_string(),
+                keyword: "anyOf".to_string(),
+                schema_path: "/properties/license/anyOf".to_string(),
+                message: "1 is not valid under any of the schemas".to_string(),
+                severity: Severity::Warning,
+                sub_errors: vec![ValidationEntry {
+                    pointer: "/license".to_string(),
+                    keyword: "type".to_string(),
+                    schema_path: "/properties/license/anyOf/0/type".to_string(),
+                    message: "1 is not of type \"string\"".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        ]}
+    }
+
+    #[test]
+    fn test_report_counts() {
+        let report = report();
+        assert!(!report.is_valid());
+        assert_eq!(report.errors().count(), 1);
+        assert_eq!(report.warnings().count(), 1);
+        assert!(ValidationReport::default().is_valid());
+    }
+
+    #[test]
+    fn test_render_text() {
+        let text = report().render(ReportFormat::Text, None);
+        assert_eq!(text, "error [type] /model_details/name: 1 is not of type \"string\"\n\
+            warning [anyOf] /license: 1 is not valid under any of the schemas\n    \
+            error [type] /license: 1 is not of type \"string\"\n");
+    }
+
+    #[test]
+    fn test_render_json_and_sarif() {
+        let json: Value = serde_json::from_str(&report().render(ReportFormat::Json, None)).unwrap();
+        assert_eq!(json["errors"], json!(1));
+        assert_eq!(json["entries"][1]["severity"], json!("warning"));
+        assert_eq!(json["entries"][1]["sub_errors"][0]["schema_path"], json!("/properties/license/anyOf/0/type"));
+
+        let sarif = report().to_sarif(Some("modelcard.json"));
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], json!("type"));
+        assert_eq!(result["level"], json!("error"));
+        assert_eq!(result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], json!("modelcard.json"));
+        assert_eq!(result["locations"][0]["logicalLocations"][0]["fullyQualifiedName"], json!("/model_details/name"));
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_render_location() {
+        let mut report = report();
+        report.entries[0].location = Some(SourceLocation { file: "layers/model.json".to_string(), line: Some(3), column: Some(5) });
+        let text = report.render(ReportFormat::Text, None);
+        assert!(text.starts_with("error [type] /model_details/name: 1 is not of type \"string\"\n  --> layers/model.json:3:5\n"), "{}", text);
+
+        let sarif = report.to_sarif(Some("modelcard.json"));
+        let location = &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], json!("layers/model.json"));
+        assert_eq!(location["region"], json!({"startLine": 3, "startColumn": 5}));
+        let json = report.to_json();
+        assert_eq!(json["entries"][0]["location"], json!({"file": "layers/model.json", "line": 3, "column": 5}));
+        assert!(json["entries"][1].get("location").is_none());
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("SARIF".parse::<ReportFormat>().unwrap(), ReportFormat::Sarif);
+        assert!("xml".parse::<ReportFormat>().is_err());
+    }
+}
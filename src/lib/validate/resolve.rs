@@ -0,0 +1,310 @@
+//! Offline resolution of schema references.
+//!
+//! Schemas split into several files reference each other with relative `$ref`s (`common.json#/$defs/owner`)
+//! or by the `$id` of the referenced schema (`https://example.org/schemas/common.json`). References are
+//! resolved against the base URI of the referring schema: the `file://` URI of the schema file or its `$id`.
+//! Schemas are looked up by `$id` in the directory of the schema file and the directories of the schema search
+//! path, and paths below the `$id` of such a schema are read from the directory of its file. Any other reference
+//! fails, remote schemas are never fetched over the network.
+
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+use anyhow::{Context, Result};
+use jsonschema::{Retrieve, Uri, Validator};
+use serde_json::Value;
+
+use crate::utils::{load_json_file, DataFormat};
+
+/// Where the references of a schema are resolved from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaOptions {
+    /// The schema file, relative references are resolved from its directory.
+    pub file: Option<PathBuf>,
+    /// Directories searched for referenced schemas, by `$id` or by file name.
+    pub search_path: Vec<PathBuf>,
+}
+
+impl SchemaOptions {
+    /// Resolve references relative to the given schema file.
+    pub fn from_file(file: impl Into<PathBuf>) -> Self {
+        SchemaOptions { file: Some(file.into()), ..Default::default() }
+    }
+
+    /// Add directories to the schema search path.
+    pub fn with_search_path<P: Into<PathBuf>>(mut self, dirs: impl IntoIterator<Item = P>) -> Self {
+        self.search_path.extend(dirs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Load the schema file.
+    ///
+    /// ## Errors
+    ///
+    /// The function will return an error if no schema file is set or it can not be loaded.
+    pub fn load(&self) -> Result<Value> {
+        let file = self.file.as_ref().context("No schema file given")?;
+        load_json_file(file).with_context(|| format!("Could not load schema '{}'", file.display()))
+    }
+
+    /// The directory of the schema file followed by the search path.
+    fn dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![];
+        if let Some(dir) = self.file.as_ref().and_then(|file| absolute(file).parent().map(Path::to_path_buf)) {
+            dirs.push(dir);
+        }
+        dirs.extend(self.search_path.iter().map(|dir| absolute(dir)));
+        dirs
+    }
+
+    /// Compile a schema with a retriever reading referenced schemas from the local file system.
+    ///
+    /// The draft is selected by the `$schema` keyword of the schema.
+    ///
+    /// ## Errors
+    ///
+    /// The function will return an error if the schema is invalid or a reference can not be resolved.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use modelcards::validate::SchemaOptions;
+    ///
+    /// let options = SchemaOptions::from_file("schema/modelcard.json").with_search_path(["schemas/common"]);
+    /// let validator = options.validator(&options.load().unwrap()).unwrap();
+    /// ```
+    pub fn validator(&self, schema: &Value) -> Result<Validator> {
+        let retriever = FileRetriever::new(self.dirs());
+        let mut options = jsonschema::options().with_retriever(retriever);
+        if let Some(file) = &self.file {
+            options = options.with_base_uri(file_uri(&absolute(file)));
+        }
+        options.build(schema).map_err(|e| anyhow::anyhow!("Could not compile schema: {}", e))
+    }
+}
+
+/// Resolves references to schema files in local directories.
+struct FileRetriever {
+    /// Schema files of the directories by their `$id`.
+    ids: HashMap<String, PathBuf>,
+    /// Base URIs of the `$id`s (up to the last `/`) with the directory of their schema file, longest first.
+    bases: Vec<(String, PathBuf)>,
+}
+
+impl FileRetriever {
+    fn new(dirs: Vec<PathBuf>) -> Self {
+        let mut ids = HashMap::new();
+        for dir in &dirs {
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    log::warn!("Could not read schema directory '{}': {}", dir.display(), e);
+                    continue;
+                }
+            };
+            for file in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+                if !file.is_file() || !matches!(DataFormat::from_path(&file), Some(DataFormat::Json | DataFormat::Yaml)) {
+                    continue;
+                }
+                let id = load_json_file(&file).ok()
+                    .and_then(|schema| schema.get("$id").and_then(Value::as_str).map(without_fragment));
+                if let Some(id) = id {
+                    ids.entry(id).or_insert(file);
+                }
+            }
+        }
+        let mut bases: Vec<(String, PathBuf)> = ids.iter()
+            .filter(|(id, _)| id.contains("://"))
+            .filter_map(|(id, file)| Some((id[..=id.rfind('/')?].to_string(), file.parent()?.to_path_buf())))
+            .collect();
+        bases.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        bases.dedup_by(|a, b| a.0 == b.0);
+        FileRetriever { ids, bases }
+    }
+
+    /// Find the schema file of a reference, already resolved against the base URI of the referring schema:
+    /// a known `$id`, a `file://` URI or a path below the base URI of a known `$id`.
+    fn find(&self, uri: &str) -> Result<PathBuf, String> {
+        let uri = without_fragment(uri);
+        if let Some(file) = self.ids.get(&uri) {
+            return Ok(file.clone());
+        }
+        if let Some(path) = uri.strip_prefix("file://") {
+            let file = PathBuf::from(percent_decode(path.trim_start_matches(|c| c != '/')));
+            if file.is_file() {
+                return Ok(file);
+            }
+        }
+        for (base, dir) in &self.bases {
+            let Some(path) = uri.strip_prefix(base.as_str()) else {
+                continue;
+            };
+            let segments: Vec<String> = path.split('/').map(percent_decode).collect();
+            if segments.iter().any(|segment| segment == ".." || segment == "." || segment.is_empty()) {
+                return Err(format!("unresolvable reference (offline): '{}' leaves the directory of '{}'", uri, base));
+            }
+            let file = dir.join(segments.iter().collect::<PathBuf>());
+            if file.is_file() {
+                return Ok(file);
+            }
+        }
+        Err(format!("unresolvable reference (offline): schema '{}' is not a `$id` or file in the schema directory or search path", uri))
+    }
+}
+
+impl Retrieve for FileRetriever {
+    fn retrieve(&self, uri: &Uri<String>) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let file = self.find(uri.as_str())?;
+        log::debug!("Resolved schema '{}' to '{}'", uri.as_str(), file.display());
+        Ok(load_json_file(&file)?)
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    path.canonicalize().or_else(|_| std::path::absolute(path)).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn without_fragment(uri: &str) -> String {
+    uri.split('#').next().unwrap_or_default().to_string()
+}
+
+/// The `file://` URI of an absolute path.
+fn file_uri(path: &Path) -> String {
+    let path = crate::utils::strip_unc(path).replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for c in path.chars() {
+        match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '/' | '-' | '.' | '_' | '~' | ':' => uri.push(c),
+            c => {
+                let mut buffer = [0; 4];
+                for byte in c.encode_utf8(&mut buffer).bytes() {
+                    uri.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+    uri
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%' && i + 2 < bytes.len())
+            .then(|| std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|h| u8::from_str_radix(h, 16).ok()))
+            .flatten();
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::create_file, validate::validate_report_with};
+    use serde_json::json;
+    use std::{env::temp_dir, fs::{create_dir_all, remove_dir_all}};
+
+    fn get_temp_dir(path: &str, create: bool) -> PathBuf {
+        let mut dir = temp_dir();
+        dir.push(path);
+        if dir.exists() {
+            remove_dir_all(&dir).expect("Could not free test directory");
+        }
+        if create {
+            create_dir_all(&dir).expect("Could not create test directory");
+        }
+        dir
+    }
+
+    #[test]
+    fn test_resolve_relative_and_id_references() {
+        let dir = get_temp_dir("test_resolve_schema_references", true);
+        let schemas = dir.join("org schema");
+        let shared = dir.join("shared");
+        create_dir_all(schemas.join("defs")).unwrap();
+        create_dir_all(&shared).unwrap();
+        create_file(&schemas.join("modelcard.json"), r#"{
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "owner": {"$ref": "common.json#/$defs/owner"},
+                "version": {"$ref": "defs/version.json"},
+                "metrics": {"type": "array", "items": {"$ref": "https://example.org/schemas/metrics.json"}}
+            }
+        }"#).unwrap();
+        create_file(&schemas.join("common.json"), r#"{"$defs": {"owner": {"type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}}}}"#).unwrap();
+        create_file(&schemas.join("defs/version.json"), r#"{"type": "string", "pattern": "^[0-9]+\\.[0-9]+$"}"#).unwrap();
+        create_file(&shared.join("metrics.json"), r#"{"$id": "https://example.org/schemas/metrics.json", "type": "object", "required": ["type", "value"]}"#).unwrap();
+
+        let options = SchemaOptions::from_file(schemas.join("modelcard.json")).with_search_path([&shared]);
+        let schema = options.load().unwrap();
+        let valid = json!({"owner": {"name": "Jane"}, "version": "1.0", "metrics": [{"type": "accuracy", "value": 0.9}]});
+        assert!(validate_report_with(&valid, Some(schema.clone()), &options).unwrap().is_valid());
+
+        let invalid = json!({"owner": {}, "version": "one", "metrics": [{"type": "accuracy"}]});
+        let report = validate_report_with(&invalid, Some(schema.clone()), &options).unwrap();
+        let pointers: Vec<&str> = report.entries.iter().map(|entry| entry.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/metrics/0", "/owner", "/version"]);
+
+        // the `$id` of metrics.json is unknown without the search path and is not fetched
+        let error = validate_report_with(&valid, Some(schema), &SchemaOptions::from_file(schemas.join("modelcard.json"))).unwrap_err();
+        assert!(error.to_string().contains("https://example.org/schemas/metrics.json"), "{}", error);
+        remove_dir_all(&dir).expect("Failed to remove test directory");
+    }
+
+    #[test]
+    fn test_resolve_relative_references_of_schema_with_id() {
+        let dir = get_temp_dir("test_resolve_schema_with_id", true);
+        create_file(&dir.join("modelcard.json"), r#"{"$id": "https://example.org/cards/modelcard.json", "$ref": "common.json"}"#).unwrap();
+        create_file(&dir.join("common.json"), r#"{"type": "object", "required": ["name"]}"#).unwrap();
+
+        let options = SchemaOptions::from_file(dir.join("modelcard.json"));
+        let schema = options.load().unwrap();
+        assert!(validate_report_with(&json!({"name": "Model"}), Some(schema.clone()), &options).unwrap().is_valid());
+        assert!(!validate_report_with(&json!({}), Some(schema), &options).unwrap().is_valid());
+        remove_dir_all(&dir).expect("Failed to remove test directory");
+    }
+
+    #[test]
+    fn test_reject_unknown_and_escaping_references() {
+        let dir = get_temp_dir("test_reject_unknown_schema_references", true);
+        let schemas = dir.join("schemas");
+        create_dir_all(&schemas).unwrap();
+        create_file(&schemas.join("common.json"), r#"{"type": "object"}"#).unwrap();
+        create_file(&dir.join("secret.json"), r#"{"type": "object"}"#).unwrap();
+        create_file(&schemas.join("modelcard.json"), r#"{"$id": "https://example.org/cards/modelcard.json"}"#).unwrap();
+
+        let retriever = FileRetriever::new(vec![schemas.clone()]);
+        assert_eq!(retriever.find("https://example.org/cards/common.json#/$defs/owner"), Ok(schemas.join("common.json")));
+        // an unrelated reference is not mapped to a local file of the same name
+        let error = retriever.find("https://other.org/schemas/common.json").unwrap_err();
+        assert!(error.starts_with("unresolvable reference (offline)"), "{}", error);
+        assert!(retriever.find("https://example.org/cards/%2E%2E/secret.json").is_err());
+        assert!(retriever.find("https://example.org/cards/missing.json").is_err());
+
+        let options = SchemaOptions::from_file(schemas.join("card.json"));
+        create_file(&schemas.join("card.json"), r#"{"$ref": "https://other.org/schemas/common.json"}"#).unwrap();
+        let error = validate_report_with(&json!({}), Some(options.load().unwrap()), &options).unwrap_err();
+        assert!(format!("{:?}", error).contains("unresolvable reference (offline)"), "{:?}", error);
+        remove_dir_all(&dir).expect("Failed to remove test directory");
+    }
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(file_uri(Path::new("/tmp/org schema/card.json")), "file:///tmp/org%20schema/card.json");
+        assert_eq!(percent_decode("/tmp/org%20schema/%C3%A4.json"), "/tmp/org schema/ä.json");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
@@ -0,0 +1,257 @@
+//! Completeness scoring of modelcards.
+//!
+//! Most fields of a modelcard schema are optional, so a nearly empty card passes validation. The score counts
+//! the optional fields declared by the schema for each section (a top level object property like
+//! `model_details`) and how many of them are filled in the modelcard.
+//!
+//! Fields of nested objects (e.g. `model_details/version/name`) are counted individually, arrays count as a
+//! single field. A field is filled if it is present and not `null`, an empty string, array or object.
+
+use std::fmt;
+use serde::Serialize;
+use serde_json::{json, Value};
+use anyhow::{bail, Result};
+
+use crate::{assets, merge::escape_pointer_token};
+
+/// Maximum nesting of objects followed, guards against recursive schemas.
+const MAX_DEPTH: usize = 8;
+
+/// Completeness of a section of a modelcard.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SectionScore {
+    /// Name of the section, e.g. `model_details`.
+    pub name: String,
+    /// Number of optional fields filled.
+    pub filled: usize,
+    /// Number of optional fields declared by the schema.
+    pub total: usize,
+    /// JSON pointers of the optional fields not filled.
+    pub missing: Vec<String>,
+}
+
+impl SectionScore {
+    /// Percentage of the optional fields filled, a section without optional fields is complete.
+    pub fn percent(&self) -> f64 {
+        percent(self.filled, self.total)
+    }
+}
+
+/// Completeness of a modelcard per section.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Score {
+    /// The top-level object properties of the schema, sorted by name.
+    pub sections: Vec<SectionScore>,
+}
+
+impl Score {
+    /// Number of optional fields filled in all sections.
+    pub fn filled(&self) -> usize {
+        self.sections.iter().map(|section| section.filled).sum()
+    }
+
+    /// Number of optional fields declared in all sections.
+    pub fn total(&self) -> usize {
+        self.sections.iter().map(|section| section.total).sum()
+    }
+
+    /// Percentage of the optional fields filled in all sections.
+    pub fn percent(&self) -> f64 {
+        percent(self.filled(), self.total())
+    }
+
+    /// Whether the total percentage reaches the given minimum percentage.
+    pub fn meets(&self, minimum: f64) -> bool {
+        self.percent() >= minimum
+    }
+
+    /// Render the score as JSON.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "percent": round(self.percent()),
+            "filled": self.filled(),
+            "total": self.total(),
+            "sections": self.sections.iter().map(|section| json!({
+                "name": section.name,
+                "percent": round(section.percent()),
+                "filled": section.filled,
+                "total": section.total,
+                "missing": section.missing,
+            })).collect::<Vec<Value>>(),
+        })
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.sections.iter().map(|section| section.name.len()).max().unwrap_or(0).max("total".len());
+        for section in &self.sections {
+            writeln!(f, "{:width$}  {:>4}/{:<4} {:>5.1}%", section.name, section.filled, section.total, section.percent(), width = width)?;
+            for pointer in &section.missing {
+                writeln!(f, "{:width$}    missing {}", "", pointer, width = width)?;
+            }
+        }
+        writeln!(f, "{:width$}  {:>4}/{:<4} {:>5.1}%", "total", self.filled(), self.total(), self.percent(), width = width)
+    }
+}
+
+fn percent(filled: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        filled as f64 * 100.0 / total as f64
+    }
+}
+
+fn round(percent: f64) -> f64 {
+    (percent * 10.0).round() / 10.0
+}
+
+/// Score the completeness of a model card against a schema.
+///
+/// If no schema is given, the built-in Google model card schema is used. Only local references
+/// (`#/definitions/...`) of the schema are followed.
+///
+/// ## Errors
+///
+/// The function will return an error if the built-in schema can not be parsed or the schema declares no sections.
+///
+/// ## Example
+///
+/// ```rust
+/// use serde_json::json;
+/// use modelcards::validate::score_against_schema;
+///
+/// let modelcard = json!({"model_details": {"name": "My Model", "version": {"name": "1.0"}}});
+/// let score = score_against_schema(&modelcard, None).unwrap();
+/// let section = &score.sections.iter().find(|section| section.name == "model_details").unwrap();
+/// assert_eq!(section.filled, 2);
+/// assert!(section.missing.contains(&"/model_details/overview".to_string()));
+/// assert!(!score.meets(50.0));
+/// ```
+///
+pub fn score_against_schema(modelcard: &Value, schema: Option<Value>) -> Result<Score> {
+    let schema = match schema {
+        Some(s) => s,
+        None => serde_json::from_str(assets::schema::get_schema())?,
+    };
+    let mut score = Score::default();
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (name, property) in properties {
+            let property = resolve(&schema, property);
+            if property.get("properties").is_none() {
+                continue;
+            }
+            let mut fields = vec![];
+            let pointer = format!("/{}", escape_pointer_token(name));
+            collect_fields(&schema, property, modelcard.get(name), &pointer, 0, &mut fields);
+            score.sections.push(SectionScore {
+                name: name.clone(),
+                filled: fields.iter().filter(|(_, filled)| *filled).count(),
+                total: fields.len(),
+                missing: fields.into_iter().filter(|(_, filled)| !filled).map(|(pointer, _)| pointer).collect(),
+            });
+        }
+    }
+    if score.sections.is_empty() {
+        bail!("Schema declares no sections to score");
+    }
+    Ok(score)
+}
+
+/// Follow a local `$ref` of a schema node.
+pub(super) fn resolve<'a>(root: &'a Value, node: &'a Value) -> &'a Value {
+    match node.get("$ref").and_then(Value::as_str).and_then(|reference| reference.strip_prefix('#')) {
+        Some(pointer) => root.pointer(pointer).unwrap_or(node),
+        None => node,
+    }
+}
+
+/// Collect the optional fields of an object schema with whether they are filled.
+fn collect_fields(root: &Value, node: &Value, value: Option<&Value>, pointer: &str, depth: usize, fields: &mut Vec<(String, bool)>) {
+    let properties = match node.get("properties").and_then(Value::as_object) {
+        Some(properties) => properties,
+        None => return,
+    };
+    let required: Vec<&str> = node.get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    for (key, property) in properties {
+        let property = resolve(root, property);
+        let child = format!("{}/{}", pointer, escape_pointer_token(key));
+        let child_value = value.and_then(|value| value.get(key));
+        if property.get("properties").is_some() && depth < MAX_DEPTH {
+            // required objects are enforced by validation, but their optional fields still count
+            collect_fields(root, property, child_value, &child, depth + 1, fields);
+        } else if !required.contains(&key.as_str()) {
+            fields.push((child, is_filled(child_value)));
+        }
+    }
+}
+
+fn is_filled(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::String(s)) => !s.trim().is_empty(),
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(Value::Object(map)) => !map.is_empty(),
+        Some(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_custom_schema() {
+        let schema = json!({
+            "properties": {
+                "version": {"type": "string"},
+                "details": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": {"type": "string"},
+                        "overview": {"type": "string"},
+                        "owners": {"type": "array"},
+                        "version": {"$ref": "#/definitions/version"}
+                    }
+                },
+                "notes": {"type": "object", "properties": {"text": {"type": "string"}}}
+            },
+            "definitions": {"version": {"type": "object", "properties": {"name": {"type": "string"}, "date": {"type": "string"}}}}
+        });
+        let modelcard = json!({"details": {"name": "Model", "overview": "", "owners": [{"name": "Jane"}], "version": {"name": "1.0", "date": null}}});
+        let score = score_against_schema(&modelcard, Some(schema)).unwrap();
+        let names: Vec<&str> = score.sections.iter().map(|section| section.name.as_str()).collect();
+        assert_eq!(names, vec!["details", "notes"]);
+        assert_eq!(score.sections[0].total, 4);
+        assert_eq!(score.sections[0].filled, 2);
+        assert_eq!(score.sections[0].missing, vec!["/details/overview", "/details/version/date"]);
+        assert_eq!(score.sections[1].percent(), 0.0);
+        assert_eq!(score.filled(), 2);
+        assert_eq!(score.total(), 5);
+        assert!(score.meets(40.0));
+        assert!(!score.meets(40.1));
+        assert_eq!(score.to_json()["sections"][0]["percent"], json!(50.0));
+    }
+
+    #[test]
+    fn test_score_google_sample() {
+        let sample: Value = serde_json::from_str(assets::schema::get_sample()).unwrap();
+        let score = score_against_schema(&sample, None).unwrap();
+        let names: Vec<&str> = score.sections.iter().map(|section| section.name.as_str()).collect();
+        assert_eq!(names, vec!["considerations", "model_details", "model_parameters", "quantitative_analysis"]);
+        assert!(score.percent() > 50.0, "{}", score);
+        let empty = score_against_schema(&json!({}), None).unwrap();
+        assert_eq!(empty.filled(), 0);
+        assert_eq!(empty.total(), score.total());
+    }
+
+    #[test]
+    fn test_score_without_sections() {
+        assert!(score_against_schema(&json!({}), Some(json!({"type": "object"}))).is_err());
+    }
+}
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use cli::{Cli, Command};
 use settings::Settings;
-use modelcards::utils::console;
+use modelcards::{merge::MergeOptions, utils::console};
 
 use clap::{CommandFactory, Parser};
 
@@ -23,19 +23,43 @@ fn main() {
     // Precedence: defaults < config.toml < env vars (MC_*) < CLI args
     let mut overrides: Vec<(&str, String)> = vec![];
     match &cli.command {
-        Command::Build { source, target, .. } => {
+        Command::Build { source, target, min_score, profile, .. } => {
             if let Some(s) = source {
                 overrides.push(("input.data", s.clone()));
             }
             if let Some(t) = target {
                 overrides.push(("output.target", t.clone()));
             }
+            if let Some(m) = min_score {
+                overrides.push(("output.min_score", m.to_string()));
+            }
+            if let Some(p) = profile {
+                overrides.push(("input.profile", p.to_string()));
+            }
         }
-        Command::Check { source: Some(s) } => {
-            overrides.push(("input.data", s.clone()));
+        Command::Check { source, profile, .. } => {
+            if let Some(s) = source {
+                overrides.push(("input.data", s.clone()));
+            }
+            if let Some(p) = profile {
+                overrides.push(("input.profile", p.to_string()));
+            }
         }
-        Command::Validate { schema: Some(s), .. } => {
-            overrides.push(("input.schema", s.clone()));
+        Command::Validate { schema, profile, .. } => {
+            if let Some(s) = schema {
+                overrides.push(("input.schema", s.clone()));
+            }
+            if let Some(p) = profile {
+                overrides.push(("input.profile", p.to_string()));
+            }
+        }
+        Command::Score { schema, min_score, .. } => {
+            if let Some(s) = schema {
+                overrides.push(("input.schema", s.clone()));
+            }
+            if let Some(m) = min_score {
+                overrides.push(("output.min_score", m.to_string()));
+            }
         }
         Command::Render { template: Some(t), .. } => {
             overrides.push(("output.template", t.clone()));
@@ -50,49 +74,183 @@ fn main() {
     log::debug!("Settings: {:?}", settings);
 
     match cli.command {
-        Command::Merge { sources, target } => {
-            log::debug!("Merge sources={:?}, target={:?}", sources, target);
-            if let Err(e) = cmd::merge_modelcards(sources, target) {
-                console::error_exit("Could not merge modelcards", Some(e));
+        Command::Merge { sources, target, arrays, merge_mode, input_format, numeric_prefixes, explain, strict, base, conflict_markers } => {
+            log::debug!("Merge sources={:?}, target={:?}, arrays={:?}, merge_mode={}, explain={}, strict={}", sources, target, arrays, merge_mode, explain, strict);
+            let mut options = cmd::merge_options(merge_mode, input_format, numeric_prefixes, strict, &arrays).unwrap_or_else(|e| {
+                console::error_exit("Invalid merge options", Some(e));
+                unreachable!();
+            });
+            options.interpolate = true;
+            options.variables.insert("project_dir".to_string(), cli_dir.display().to_string());
+            if let Some(base) = base {
+                let to_stdout = target.is_none();
+                match cmd::merge_three_way(base, sources, target, &options, conflict_markers) {
+                    Ok((merged, conflicts)) => {
+                        // without a target the merged modelcard is printed, conflicts or not
+                        if to_stdout {
+                            println!("{}", merged);
+                        }
+                        if conflicts.is_empty() {
+                            console::success_exit("Modelcards successfully merged!");
+                        }
+                        for conflict in &conflicts {
+                            console::error(&format!("Conflict at {}", conflict), None::<String>);
+                        }
+                        console::error_exit(&format!("Modelcards merged with {} conflicts", conflicts.len()), None::<String>);
+                    }
+                    Err(e) => console::error_exit("Could not merge modelcards", Some(e)),
+                }
+            } else {
+                // without a target the merged modelcard is printed, like by a three-way merge
+                let to_stdout = target.is_none();
+                let merged = if explain {
+                    // the explanation goes to stderr, so it does not mix with a modelcard piped to stdout
+                    let (merged, explanation) = cmd::explain_modelcards(sources, target, options).unwrap_or_else(|e| {
+                        console::error_exit("Could not explain modelcards", Some(e));
+                        unreachable!();
+                    });
+                    eprintln!("{}", explanation);
+                    merged
+                } else {
+                    cmd::merge_modelcards(sources, target, options).unwrap_or_else(|e| {
+                        console::error_exit("Could not merge modelcards", Some(e));
+                        unreachable!();
+                    })
+                };
+                if to_stdout {
+                    println!("{}", merged);
+                }
+                console::success_exit("Modelcards successfully merged!");
             }
-            console::success_exit("Modelcards successfully merged!");
         },
-        Command::Validate { sources, .. } => {
-            log::debug!("Validate data={:?}, schema={:?}", sources, settings.input.schema);
-            match cmd::validate_modelcard(sources, settings.input.schema) {
-                Ok(true) => console::success_exit("Modelcard is valid!"),
-                Ok(false) => console::success_exit("Modelcard is not valid!"),
+        Command::Validate { sources, schema_path, format, deny_warnings, strict, merge_mode, input_format, numeric_prefixes, .. } => {
+            log::debug!("Validate data={:?}, schema={:?}, profile={}, merge_mode={}, format={}, strict={}", sources, settings.input.schema, settings.input.profile, merge_mode, format, strict);
+            let deny_warnings = deny_warnings || strict;
+            let mut options = MergeOptions { mode: merge_mode, format: input_format, numeric_prefixes, strict, ..Default::default() };
+            options.interpolate = true;
+            options.variables.insert("project_dir".to_string(), cli_dir.display().to_string());
+            let artifact = sources.last().cloned();
+            let search_path: Vec<String> = settings.input.schema_path.into_iter().chain(schema_path).collect();
+            match cmd::validate_modelcard(sources, settings.input.schema, &search_path, settings.input.profile, &options) {
+                Ok(report) => {
+                    if let Err(e) = cmd::print_report(&report, format, artifact.as_deref()) {
+                        console::error_exit("Could not print validation report", Some(e));
+                    }
+                    if !report.is_valid() {
+                        console::error_exit("Modelcard is not valid!", None::<String>);
+                    } else if deny_warnings && report.has_warnings() {
+                        console::warning_exit("Modelcard is valid, but has warnings!");
+                    } else {
+                        console::success_exit("Modelcard is valid!");
+                    }
+                }
                 Err(e) => console::error_exit("Could not validate modelcard!", Some(e)),
             }
         },
-        Command::Render { sources, .. } => {
-            log::debug!("Render data={:?}, template={:?}", sources, settings.output.template);
-            match cmd::render_modelcard(sources, settings.output.template) {
+        Command::Lint { sources, format, deny_warnings, merge_mode, input_format, numeric_prefixes } => {
+            log::debug!("Lint data={:?}, rules={:?}, merge_mode={}, format={}", sources, settings.lint, merge_mode, format);
+            let mut options = MergeOptions { mode: merge_mode, format: input_format, numeric_prefixes, ..Default::default() };
+            options.interpolate = true;
+            options.variables.insert("project_dir".to_string(), cli_dir.display().to_string());
+            let artifact = sources.last().cloned();
+            match cmd::lint_modelcard(sources, &settings.lint, &options) {
+                Ok(report) => {
+                    if let Err(e) = cmd::print_report(&report, format, artifact.as_deref()) {
+                        console::error_exit("Could not print lint report", Some(e));
+                    }
+                    if !report.is_valid() {
+                        console::error_exit("Modelcard failed lint rules!", None::<String>);
+                    } else if deny_warnings && report.has_warnings() {
+                        console::warning_exit("Modelcard passed all lint rules, but has warnings!");
+                    } else {
+                        console::success_exit("Modelcard passed all lint rules!");
+                    }
+                }
+                Err(e) => console::error_exit("Could not lint modelcard!", Some(e)),
+            }
+        },
+        Command::Score { sources, json, merge_mode, input_format, numeric_prefixes, .. } => {
+            log::debug!("Score data={:?}, schema={:?}, min_score={:?}, merge_mode={}", sources, settings.input.schema, settings.output.min_score, merge_mode);
+            let mut options = MergeOptions { mode: merge_mode, format: input_format, numeric_prefixes, ..Default::default() };
+            options.interpolate = true;
+            options.variables.insert("project_dir".to_string(), cli_dir.display().to_string());
+            match cmd::score_modelcard(sources, settings.input.schema, &options) {
+                Ok(score) => {
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&score.to_json()).unwrap_or_default());
+                    } else {
+                        print!("{}", score);
+                    }
+                    match settings.output.min_score {
+                        Some(min_score) if !score.meets(min_score) => console::error_exit(&format!("Modelcard is only {:.1}% complete, the minimum is {}%!", score.percent(), min_score), None::<String>),
+                        _ => console::success_exit(&format!("Modelcard is {:.1}% complete!", score.percent())),
+                    }
+                }
+                Err(e) => console::error_exit("Could not score modelcard!", Some(e)),
+            }
+        },
+        Command::Render { sources, target, merge_mode, input_format, numeric_prefixes, .. } => {
+            log::debug!("Render data={:?}, template={:?}, merge_mode={}", sources, settings.output.template, merge_mode);
+            let mut options = MergeOptions { mode: merge_mode, format: input_format, numeric_prefixes, ..Default::default() };
+            options.interpolate = true;
+            options.variables.insert("project_dir".to_string(), cli_dir.display().to_string());
+            match cmd::render_modelcard(sources, settings.output.template, target, &options) {
                 Ok(true) => console::success_exit("Modelcard successfully rendered!"),
                 Ok(false) => console::success_exit("Could not render modelcard!"),
                 Err(e) => console::error_exit("Could not render modelcard!", Some(e)),
             }
         },
+        Command::Migrate { sources, target, to, from, migrations, input_format, numeric_prefixes } => {
+            log::debug!("Migrate sources={:?}, target={:?}, to={:?}, from={:?}, migrations={:?}", sources, target, to, from, migrations);
+            let options = cmd::MigrateOptions { target, to, from, migrations, format: input_format, numeric_prefixes };
+            match cmd::migrate_modelcards(sources, &options) {
+                Ok(summary) => {
+                    for line in &summary {
+                        console::info(line);
+                    }
+                    console::success_exit("Modelcards successfully migrated!");
+                }
+                Err(e) => console::error_exit("Could not migrate modelcards", Some(e)),
+            }
+        },
         Command::Init { name, force } => {
             if let Err(e) = cmd::create_new_project(&name, force) {
                 console::error_exit("Could not create project", Some(e));
             }
         },
-        Command::Build { force, .. } => {
-            log::debug!("Build source={:?}, target={:?}, force={:?}", settings.input.data, settings.output.target, force);
-            let force = force.unwrap_or(settings.force);
-            if let Err(e) = cmd::build_project(&cli_dir, Some(settings.input.data), Some(settings.output.target), force) {
-                console::error_exit("Could not build project", Some(e));
+        Command::Build { force, strict, deny_warnings, .. } => {
+            log::debug!("Build source={:?}, target={:?}, force={:?}, strict={}, deny_warnings={}, profile={}", settings.input.data, settings.output.target, force, strict, deny_warnings, settings.input.profile);
+            let options = cmd::BuildOptions {
+                force: force.unwrap_or(settings.force),
+                strict,
+                deny_warnings,
+                min_score: settings.output.min_score,
+                schema_path: settings.input.schema_path,
+                profile: settings.input.profile,
+            };
+            match cmd::build_project(&cli_dir, Some(settings.input.data), Some(settings.output.target), &options) {
+                Ok(true) => console::success_exit("Project successfully buildt!"),
+                Ok(false) => console::warning_exit("Project has validation warnings, modelcard not built!"),
+                Err(e) => console::error_exit("Could not build project", Some(e)),
             }
-            console::success_exit("Project successfully buildt!");
         },
-        Command::Check { .. } => {
-            log::debug!("Check source={:?}", settings.input.data);
-            let valid = cmd::check_project(&cli_dir, Some(settings.input.data));
-            if valid.is_ok() {
-                console::success_exit("Project is valid!");
-            } else {
-                console::error_exit("Project could not be validated!", valid.err());
+        Command::Check { format, deny_warnings, strict, .. } => {
+            log::debug!("Check source={:?}, format={}, profile={}, deny_warnings={}, strict={}", settings.input.data, format, settings.input.profile, deny_warnings, strict);
+            let deny_warnings = deny_warnings || strict;
+            match cmd::check_project(&cli_dir, Some(settings.input.data.clone()), &settings.input.schema_path, settings.input.profile, strict) {
+                Ok(report) => {
+                    if let Err(e) = cmd::print_report(&report, format, Some(&settings.input.data)) {
+                        console::error_exit("Could not print validation report", Some(e));
+                    }
+                    if !report.is_valid() {
+                        console::error_exit("Project is not valid!", None::<String>);
+                    } else if deny_warnings && report.has_warnings() {
+                        console::warning_exit("Project is valid, but has warnings!");
+                    } else {
+                        console::success_exit("Project is valid!");
+                    }
+                }
+                Err(e) => console::error_exit("Project could not be validated!", Some(e)),
             }
         },
         Command::Completion { shell } => {
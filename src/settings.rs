@@ -1,14 +1,19 @@
 use config::{Config, ConfigError, Environment, File};
-use modelcards::assets::config::get_default;
+use modelcards::{assets::config::get_default, lint::LintConfig, validate::Profile};
 use serde::Deserialize;
 use std::env;
-use std::path::Path;
 
 #[derive(Debug, Deserialize)]
 #[allow(unused)]
 pub struct Input {
     pub data: String,
     pub schema: Option<String>,
+    /// Directories searched for schemas referenced by `$ref`, besides the directory of the schema
+    #[serde(default)]
+    pub schema_path: Vec<String>,
+    /// Profile layered over the schema: google, huggingface, eu-ai-act, nist-ai-rmf or internal
+    #[serde(default)]
+    pub profile: Profile,
     pub validate: bool,
 }
 
@@ -18,6 +23,7 @@ pub struct Output {
     pub target: String,
     pub template: Option<String>,
     pub validate: bool,
+    pub min_score: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -28,9 +34,12 @@ pub struct Settings {
     pub force: bool,
     pub input: Input,
     pub output: Output,
+    #[serde(default)]
+    pub lint: LintConfig,
 }
 
 impl Settings {
+    #[allow(unused)]
     pub fn new(config_name: &str) -> Result<Self, ConfigError> {
         Self::with_overrides(config_name, vec![])
     }
@@ -75,6 +84,8 @@ impl Settings {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use modelcards::lint::RuleLevel;
+    use std::path::Path;
 
     #[test]
     fn test_settings_defaults() {
@@ -84,10 +95,30 @@ mod tests {
         assert_eq!(settings.project_dir, ".");
         assert_eq!(settings.input.data, "./sample.json");
         assert!(settings.input.schema.is_none());
+        assert!(settings.input.schema_path.is_empty());
+        assert_eq!(settings.input.profile, Profile::Google);
         assert!(settings.input.validate);
         assert_eq!(settings.output.target, "./cards/modelcard.md");
         assert!(settings.output.template.is_none());
         assert!(settings.output.validate);
+        assert!(settings.output.min_score.is_none());
+        assert_eq!(settings.lint, LintConfig::default());
+    }
+
+    #[test]
+    fn test_with_overrides_parses_min_score() {
+        let overrides = vec![("output.min_score", "75.5".to_string())];
+        let settings = Settings::with_overrides("nonexistent_config", overrides)
+            .expect("Could not load settings with overrides");
+        assert_eq!(settings.output.min_score, Some(75.5));
+    }
+
+    #[test]
+    fn test_with_overrides_selects_profile() {
+        let overrides = vec![("input.profile", Profile::NistAiRmf.to_string())];
+        let settings = Settings::with_overrides("nonexistent_config", overrides)
+            .expect("Could not load settings with overrides");
+        assert_eq!(settings.input.profile, Profile::NistAiRmf);
     }
 
     #[test]
@@ -148,6 +179,7 @@ project_dir = "."
 [input]
 data = "./sample.json"
 schema = "./schema/modelcard.schema.json"
+schema_path = ["./schemas", "/shared/schemas"]
 validate = true
 
 [output]
@@ -173,6 +205,7 @@ validate = true
             settings.input.schema,
             Some("./schema/modelcard.schema.json".to_string())
         );
+        assert_eq!(settings.input.schema_path, vec!["./schemas", "/shared/schemas"]);
         assert_eq!(
             settings.output.template,
             Some("./templates/modelcard.md.jinja".to_string())
@@ -181,6 +214,34 @@ validate = true
         std::fs::remove_dir_all(&tmp).ok();
     }
 
+    #[test]
+    fn test_config_file_lint_section() {
+        let tmp = std::env::temp_dir().join("mc_test_config_lint");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(
+            tmp.join("config.toml"),
+            r#"
+[lint.severity]
+owner-contact = "off"
+
+[[lint.rules]]
+id = "metric-slice"
+path = "/quantitative_analysis/performance_metrics/*"
+require = ["slice"]
+severity = "warning"
+"#,
+        )
+        .expect("Could not write test config");
+
+        let settings = Settings::new(tmp.join("config").to_str().unwrap())
+            .expect("Could not load settings from test config");
+        assert_eq!(settings.lint.severity.get("owner-contact"), Some(&RuleLevel::Off));
+        assert_eq!(settings.lint.rules[0].id, "metric-slice");
+        assert_eq!(settings.lint.rules[0].require, vec!["slice".to_string()]);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
     #[test]
     fn test_cli_overrides_beat_config_file() {
         let tmp = std::env::temp_dir().join("mc_test_cli_overrides");
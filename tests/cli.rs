@@ -0,0 +1,80 @@
+//! Runs the modelcards binary to check output and exit codes of the command line.
+
+use std::{env::temp_dir, fs::{self, create_dir, remove_dir_all}, path::{Path, PathBuf}, process::{Command, Output}};
+
+fn get_temp_dir(path: &str, create: bool) -> PathBuf {
+    let mut dir = temp_dir();
+    dir.push(path);
+    if dir.exists() {
+        remove_dir_all(&dir).expect("Could not free test directory");
+    }
+    if create {
+        create_dir(&dir).expect("Could not create test directory");
+    }
+    dir
+}
+
+fn modelcards(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_modelcards"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Could not run modelcards")
+}
+
+#[test]
+fn merge_prints_without_target() {
+    let dir = get_temp_dir("test_cli_merge_prints_without_target", true);
+    fs::write(dir.join("defaults.json"), r#"{"name": "default", "license": "MIT"}"#).unwrap();
+    fs::write(dir.join("model.json"), r#"{"name": "model"}"#).unwrap();
+
+    let output = modelcards(&dir, &["merge", "defaults.json", "model.json"]);
+    assert!(output.status.success());
+    let merged: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Merged modelcard is not printed");
+    assert_eq!(merged, serde_json::json!({"name": "model", "license": "MIT"}));
+
+    let output = modelcards(&dir, &["merge", "defaults.json", "model.json", "-o", "merged.json"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(dir.join("merged.json").exists());
+    remove_dir_all(&dir).expect("Failed to remove test directory");
+}
+
+#[test]
+fn merge_three_way_conflicts_exit_with_error() {
+    let dir = get_temp_dir("test_cli_merge_three_way_conflicts", true);
+    fs::write(dir.join("base.json"), r#"{"name": "model", "accuracy": 0.8}"#).unwrap();
+    fs::write(dir.join("ours.json"), r#"{"name": "our model", "accuracy": 0.8}"#).unwrap();
+    fs::write(dir.join("theirs.json"), r#"{"name": "their model", "accuracy": 0.9}"#).unwrap();
+
+    let output = modelcards(&dir, &["merge", "--base", "base.json", "ours.json", "theirs.json", "-o", "merged.json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Conflict at /name"), "{}", stderr);
+    let merged: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join("merged.json")).unwrap()).unwrap();
+    assert_eq!(merged, serde_json::json!({"name": "our model", "accuracy": 0.9}));
+
+    let output = modelcards(&dir, &["merge", "--base", "base.json", "ours.json", "theirs.json", "--conflict-markers"]);
+    assert_eq!(output.status.code(), Some(1));
+    let merged: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Merged modelcard is not printed");
+    assert_eq!(merged["name"], serde_json::json!({"$conflict": {"base": "model", "ours": "our model", "theirs": "their model"}}));
+
+    let output = modelcards(&dir, &["merge", "--base", "base.json", "ours.json", "ours.json"]);
+    assert!(output.status.success());
+    remove_dir_all(&dir).expect("Failed to remove test directory");
+}
+
+#[test]
+fn validate_keeps_unset_variables() {
+    let dir = get_temp_dir("test_cli_validate_keeps_unset_variables", true);
+    fs::write(dir.join("model.json"), r#"{"model_details": {"name": "Model", "overview": "Built by ${env:MODELCARDS_CLI_TEST_UNSET}"}}"#).unwrap();
+
+    let output = modelcards(&dir, &["validate", "model.json"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = modelcards(&dir, &["merge", "model.json"]);
+    let merged: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Merged modelcard is not printed");
+    assert_eq!(merged["model_details"]["overview"], "Built by ${env:MODELCARDS_CLI_TEST_UNSET}");
+    let output = modelcards(&dir, &["validate", "--strict", "model.json"]);
+    assert_eq!(output.status.code(), Some(1));
+    remove_dir_all(&dir).expect("Failed to remove test directory");
+}